@@ -4,7 +4,7 @@ mod state;
 pub use view_mode::ViewMode;
 
 use eframe::egui;
-use crate::project::Project;
+use crate::project::{FileFilter, LoadEvent, Project, ProjectLoader};
 use crate::visualization::VisualizationState;
 
 pub struct App {
@@ -13,6 +13,21 @@ pub struct App {
     pub view_mode: ViewMode,
     pub selected_file: Option<String>,
     pub show_dialog: bool,
+    /// Whether the bottom panel listing load diagnostics is open
+    pub show_diagnostics: bool,
+    /// Whether the side panel mapping tests to the code they exercise is open
+    pub show_tests: bool,
+    /// Whether the side panel with the selected element's docs is open
+    pub show_docs: bool,
+    /// Whether the side panel listing unreachable elements is open
+    pub show_dead_code: bool,
+    /// Outcome of the last attempt to save the project's config file
+    pub config_message: Option<String>,
+    /// The load running in the background, if any
+    pub loader: Option<ProjectLoader>,
+    /// The project shown before the running load started, back in place if
+    /// the load is cancelled
+    previous_project: Option<Project>,
     file_dialog: Option<crate::dialog::FileDialog>,
 }
 
@@ -24,6 +39,13 @@ impl Default for App {
             view_mode: ViewMode::Visualization,
             selected_file: None,
             show_dialog: false,
+            show_diagnostics: false,
+            show_tests: false,
+            show_docs: true,
+            show_dead_code: false,
+            config_message: None,
+            loader: None,
+            previous_project: None,
             file_dialog: None,
         }
     }
@@ -31,6 +53,8 @@ impl Default for App {
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_loader(ctx);
+        
         // Handle file dialog
         if self.show_dialog && self.file_dialog.is_none() {
             self.file_dialog = Some(crate::dialog::FileDialog::new());
@@ -54,15 +78,103 @@ impl eframe::App for App {
         // Side panel
         crate::ui::side_panel::render(self, ctx);
         
+        // Diagnostics panel, before the central panel so it takes its space
+        crate::ui::diagnostics_panel::render(self, ctx);
+        crate::ui::tests_panel::render(self, ctx);
+        crate::ui::docs_panel::render(self, ctx);
+        crate::ui::dead_code_panel::render(self, ctx);
+        
         // Central panel
         crate::ui::central_panel::render(self, ctx);
     }
 }
 
 impl App {
+    /// Opens the project at `path` with the file filter of its config file.
     fn load_project(&mut self, path: String) {
-        self.project.load_project(&path);
+        self.project.file_filter = FileFilter::load(&path);
+        self.config_message = None;
+        self.start_loading(&path);
+    }
+    
+    /// Loads the current project again in the background, e.g. after the
+    /// cfg set or the file filter changed.
+    pub fn reload_project(&mut self) {
+        if let Some(path) = self.project.project_path.clone() {
+            self.start_loading(&path);
+        }
+    }
+    
+    /// Starts loading in the background with the current cfg set and file
+    /// filter, cancelling any load still running. Until it finishes, the
+    /// project shows what has been parsed so far.
+    fn start_loading(&mut self, path: &str) {
+        let cfg = self.project.cfg.clone();
+        let file_filter = self.project.file_filter.clone();
+        if let Some(loader) = self.loader.take() {
+            loader.cancel();
+        }
+        let partial = Project {
+            project_path: Some(path.to_string()),
+            cfg: cfg.clone(),
+            file_filter: file_filter.clone(),
+            ..Project::default()
+        };
+        let previous = std::mem::replace(&mut self.project, partial);
+        // A load replacing one that never finished goes back to the project
+        // from before both
+        if self.previous_project.is_none() {
+            self.previous_project = Some(previous);
+        }
+        
+        self.loader = Some(ProjectLoader::start(path, cfg, file_filter));
         self.visualization_state = VisualizationState::default();
-        self.selected_file = self.project.files.first().cloned();
+        crate::visualization::reset_layout();
+        self.selected_file = None;
+    }
+    
+    /// Takes in what the background load has sent since the last frame.
+    fn poll_loader(&mut self, ctx: &egui::Context) {
+        let Some(loader) = &mut self.loader else {
+            return;
+        };
+        for event in loader.poll() {
+            match event {
+                LoadEvent::Progress(_) => {},
+                LoadEvent::Files(files) => {
+                    self.selected_file = files.first().cloned();
+                    self.project.files = files;
+                },
+                LoadEvent::Parsed { elements, relationships } => {
+                    self.project.elements.extend(elements);
+                    self.project.relationships.extend(relationships);
+                },
+                LoadEvent::Finished(project) => {
+                    self.project = *project;
+                    self.previous_project = None;
+                    self.loader = None;
+                    if self.selected_file.as_ref().is_none_or(|file| !self.project.files.contains(file)) {
+                        self.selected_file = self.project.files.first().cloned();
+                    }
+                    self.show_diagnostics = !self.project.diagnostics.is_empty();
+                    // Let the layout settle again with the resolved relationships
+                    self.visualization_state.animation_progress = 0.0;
+                    self.visualization_state.error_sources = None;
+                    return;
+                },
+                LoadEvent::Cancelled => {
+                    if let Some(previous) = self.previous_project.take() {
+                        self.project = previous;
+                    }
+                    self.loader = None;
+                    self.selected_file = self.project.files.first().cloned();
+                    self.visualization_state.error_sources = None;
+                    crate::visualization::reset_layout();
+                    return;
+                },
+            }
+        }
+        // Keep polling while the window is otherwise idle
+        ctx.request_repaint_after(std::time::Duration::from_millis(50));
     }
 }
@@ -1,4 +1,25 @@
-use syn::{parse_file as syn_parse_file, Item, ItemFn, ItemStruct, ItemEnum, ItemTrait, ItemImpl, ItemUse};
+mod calls;
+mod cfg;
+mod errors;
+mod imports;
+mod metrics;
+mod recover;
+mod tasks;
+mod tokens;
+mod type_refs;
+mod unsafety;
+
+pub use cfg::{mentioned_features, CfgSet, TARGET_ARCHES, TARGET_OSES};
+pub use errors::alias_error_guess;
+pub use metrics::{FunctionMetrics, Metric};
+
+use syn::spanned::Spanned;
+use tokens::tokens_to_string;
+use syn::visit::Visit;
+use syn::{
+    Item, ItemConst, ItemEnum, ItemFn, ItemForeignMod, ItemImpl, ItemMacro, ItemStatic, ItemStruct, ItemTrait, ItemType,
+    ItemUnion, ItemUse,
+};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]  // Allow unused fields for future development
@@ -7,18 +28,392 @@ pub struct CodeElement {
     pub name: String,
     pub element_type: ElementType,
     pub file_path: String,
+    /// Module the element is declared in; for a module, its own path
+    pub module_path: String,
+    pub span: SourceSpan,
+    /// How `self` is taken; only set for methods and associated functions.
+    pub receiver: Option<ReceiverKind>,
+    /// Fields or variants; only set for structs, unions and enums.
+    pub shape: Option<TypeShape>,
+    /// Size and complexity; only set for functions with a body.
+    pub metrics: Option<FunctionMetrics>,
+    pub details: ElementDetails,
+}
+
+/// Location of an element in its source file.
+///
+/// Lines and columns are both 1-based; the end position points at the last
+/// character of the item (inclusive).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceSpan {
     pub start_line: usize,
+    pub start_column: usize,
     pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceSpan {
+    pub fn of<T: Spanned>(node: &T) -> Self {
+        let span = node.span();
+        let start = span.start();
+        let end = span.end();
+
+        Self {
+            start_line: start.line,
+            start_column: start.column + 1,
+            end_line: end.line,
+            // proc-macro2 reports the column one past the last character
+            end_column: end.column.max(1),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        if self.start_line == 0 {
+            0
+        } else {
+            self.end_line.saturating_sub(self.start_line) + 1
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ElementType {
     Function,
+    Method,             // Function in an impl or trait that takes `self`
+    AssociatedFunction, // Function in an impl or trait without a receiver
     Module,
     Struct,
     Enum,
     Trait,
     Impl,
+    ExternalTrait,      // Trait from another crate, e.g. a derived `std::fmt::Debug`
+    Const,              // Module-level or associated `const`
+    Static,             // `static`, including ones declared in extern blocks
+    TypeAlias,
+    Macro,              // `macro_rules!` definition
+    Union,
+    ExternBlock,        // `extern "C" { ... }`
+    ForeignFunction,    // Function declared in an extern block
+    Task,               // Closure or async block spawned as a task or thread
+}
+
+impl ElementType {
+    pub const ALL: [ElementType; 17] = [
+        ElementType::Module,
+        ElementType::Function,
+        ElementType::Method,
+        ElementType::AssociatedFunction,
+        ElementType::Struct,
+        ElementType::Enum,
+        ElementType::Union,
+        ElementType::Trait,
+        ElementType::ExternalTrait,
+        ElementType::Impl,
+        ElementType::TypeAlias,
+        ElementType::Const,
+        ElementType::Static,
+        ElementType::Macro,
+        ElementType::ExternBlock,
+        ElementType::ForeignFunction,
+        ElementType::Task,
+    ];
+    
+    pub fn is_function(&self) -> bool {
+        matches!(
+            self,
+            ElementType::Function | ElementType::Method | ElementType::AssociatedFunction | ElementType::ForeignFunction
+        )
+    }
+    
+    /// Items a type path can name: types, type aliases and traits.
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            ElementType::Struct
+                | ElementType::Enum
+                | ElementType::Union
+                | ElementType::TypeAlias
+                | ElementType::Trait
+                | ElementType::ExternalTrait
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiverKind {
+    Ref,    // &self
+    RefMut, // &mut self
+    Value,  // self, including `self: Box<Self>` and friends
+    Static, // no receiver
+}
+
+impl ReceiverKind {
+    fn of(sig: &syn::Signature) -> Self {
+        match sig.receiver() {
+            None => ReceiverKind::Static,
+            Some(receiver) => match &*receiver.ty {
+                syn::Type::Reference(reference) if reference.mutability.is_some() => ReceiverKind::RefMut,
+                syn::Type::Reference(_) => ReceiverKind::Ref,
+                _ => ReceiverKind::Value,
+            },
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReceiverKind::Ref => "&self",
+            ReceiverKind::RefMut => "&mut self",
+            ReceiverKind::Value => "self",
+            ReceiverKind::Static => "static",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Visibility {
+    Public,     // pub
+    Crate,      // pub(crate)
+    Super,      // pub(super)
+    Restricted, // pub(in path), pub(self)
+    #[default]
+    Private,
+}
+
+impl Visibility {
+    pub const ALL: [Visibility; 5] = [
+        Visibility::Public,
+        Visibility::Crate,
+        Visibility::Super,
+        Visibility::Restricted,
+        Visibility::Private,
+    ];
+    
+    fn of(vis: &syn::Visibility) -> Self {
+        match vis {
+            syn::Visibility::Public(_) => Visibility::Public,
+            syn::Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => Visibility::Crate,
+            syn::Visibility::Restricted(restricted) if restricted.path.is_ident("super") => Visibility::Super,
+            syn::Visibility::Restricted(_) => Visibility::Restricted,
+            syn::Visibility::Inherited => Visibility::Private,
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            Visibility::Public => "pub",
+            Visibility::Crate => "pub(crate)",
+            Visibility::Super => "pub(super)",
+            Visibility::Restricted => "pub(in ..)",
+            Visibility::Private => "private",
+        }
+    }
+}
+
+/// Declaration details of an element, beyond what identifies it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementDetails {
+    pub visibility: Visibility,
+    /// Attributes other than doc comments, e.g. `derive(Debug, Clone)`
+    pub attributes: Vec<String>,
+    /// Markdown of the `///` or `//!` doc comments, with their common
+    /// indentation removed
+    pub docs: Option<String>,
+    /// Generic parameters, e.g. `'a`, `T: Clone`, `const N: usize`
+    pub generics: Vec<String>,
+    /// Rendered signature; only set for functions
+    pub signature: Option<String>,
+    pub is_async: bool,
+    pub is_const: bool,
+    pub is_unsafe: bool,
+    /// `unsafe` blocks in a function body
+    pub unsafe_blocks: usize,
+    /// Raw-pointer dereferences in the unsafe parts of a function body
+    pub raw_derefs: usize,
+    /// `.await` expressions in a function body or task, not counting those
+    /// of tasks it spawns
+    pub await_points: usize,
+    /// Error type of a returned `Result`, e.g. `io::Error`; only set for
+    /// functions and `Result` aliases
+    pub error_type: Option<String>,
+    /// `?` operators returning from a function body
+    pub try_operators: usize,
+}
+
+impl ElementDetails {
+    fn of(vis: &syn::Visibility, attrs: &[syn::Attribute], generics: &syn::Generics) -> Self {
+        Self {
+            visibility: Visibility::of(vis),
+            attributes: attrs.iter()
+                .filter(|attr| !attr.path().is_ident("doc"))
+                .map(|attr| tokens_to_string(&attr.meta))
+                .collect(),
+            docs: doc_comment(attrs),
+            generics: generics.params.iter().map(tokens_to_string).collect(),
+            ..Self::default()
+        }
+    }
+    
+    /// Adds the signature and qualifiers of a function.
+    fn with_signature(mut self, sig: &syn::Signature) -> Self {
+        let signature = tokens_to_string(sig);
+        self.signature = Some(match self.visibility {
+            Visibility::Private => signature,
+            visibility => format!("{} {}", visibility.label(), signature),
+        });
+        self.is_async = sig.asyncness.is_some();
+        self.is_const = sig.constness.is_some();
+        self.is_unsafe = sig.unsafety.is_some();
+        self.error_type = errors::ErrorType::of_output(&sig.output).map(|error_type| error_type.display());
+        self
+    }
+    
+    /// Adds the unsafe code, await points and `?` operators found in a
+    /// function body.
+    fn with_body(mut self, sig: &syn::Signature, body: &syn::Block) -> Self {
+        (self.unsafe_blocks, self.raw_derefs) = unsafety::count_unsafe(sig, body);
+        self.await_points = tasks::count_awaits(body);
+        self.try_operators = errors::count_try_operators(body);
+        self
+    }
+    
+    fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+    
+    fn with_unsafe(mut self, is_unsafe: bool) -> Self {
+        self.is_unsafe = is_unsafe;
+        self
+    }
+    
+    /// Whether the element is declared `unsafe` or has unsafe code in its body.
+    pub fn contains_unsafe(&self) -> bool {
+        self.is_unsafe || self.unsafe_blocks > 0 || self.raw_derefs > 0
+    }
+}
+
+/// Joins the `#[doc = "..."]` attributes doc comments turn into, the way
+/// rustdoc does: one line per attribute, common leading whitespace removed.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let text: Vec<String> = attrs.iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(name_value) if name_value.path.is_ident("doc") => match &name_value.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) => Some(value.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let text = text.join("\n");
+    let lines: Vec<&str> = text.lines().collect();
+    
+    let indent = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()?;
+    let docs = lines.iter()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()).trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(docs.trim_matches('\n').to_string())
+}
+
+/// The data layout of a struct, union or enum.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeShape {
+    Struct(Fields), // Unions are recorded as structs with named fields
+    Enum(Vec<Variant>),
+}
+
+impl TypeShape {
+    /// One line per field or variant, as they'd read in the source.
+    pub fn member_labels(&self) -> Vec<String> {
+        match self {
+            TypeShape::Struct(Fields::Named(fields) | Fields::Tuple(fields)) => fields.iter().map(Field::label).collect(),
+            TypeShape::Struct(Fields::Unit) => Vec::new(),
+            TypeShape::Enum(variants) => variants.iter().map(Variant::label).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fields {
+    Named(Vec<Field>),
+    Tuple(Vec<Field>), // Fields are named by their index
+    Unit,
+}
+
+impl Fields {
+    fn of(fields: &syn::Fields) -> Self {
+        let collect = |fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>| {
+            fields.iter().enumerate().map(|(index, field)| Field::of(index, field)).collect()
+        };
+        match fields {
+            syn::Fields::Named(named) => Fields::Named(collect(&named.named)),
+            syn::Fields::Unnamed(unnamed) => Fields::Tuple(collect(&unnamed.unnamed)),
+            syn::Fields::Unit => Fields::Unit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: String,
+    pub visibility: Visibility,
+}
+
+impl Field {
+    fn of(index: usize, field: &syn::Field) -> Self {
+        Self {
+            name: field.ident.as_ref().map_or_else(|| index.to_string(), |ident| ident.to_string()),
+            ty: tokens_to_string(&field.ty),
+            visibility: Visibility::of(&field.vis),
+        }
+    }
+    
+    pub fn label(&self) -> String {
+        match self.visibility {
+            Visibility::Private => format!("{}: {}", self.name, self.ty),
+            visibility => format!("{} {}: {}", visibility.label(), self.name, self.ty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Fields,
+    /// Explicit discriminant expression, as written
+    pub discriminant: Option<String>,
+}
+
+impl Variant {
+    fn of(variant: &syn::Variant) -> Self {
+        Self {
+            name: variant.ident.to_string(),
+            fields: Fields::of(&variant.fields),
+            discriminant: variant.discriminant.as_ref().map(|(_, expr)| tokens_to_string(expr)),
+        }
+    }
+    
+    pub fn label(&self) -> String {
+        let mut label = match &self.fields {
+            Fields::Named(fields) => {
+                let fields: Vec<String> = fields.iter().map(|f| format!("{}: {}", f.name, f.ty)).collect();
+                format!("{} {{ {} }}", self.name, fields.join(", "))
+            },
+            Fields::Tuple(fields) => {
+                let types: Vec<&str> = fields.iter().map(|f| f.ty.as_str()).collect();
+                format!("{}({})", self.name, types.join(", "))
+            },
+            Fields::Unit => self.name.clone(),
+        };
+        if let Some(discriminant) = &self.discriminant {
+            label = format!("{} = {}", label, discriminant);
+        }
+        label
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,240 +421,862 @@ pub struct Relationship {
     pub source_id: String,
     pub target_id: String,
     pub relationship_type: RelationshipType,
+    /// Generated by `#[derive]` rather than written out; only set on
+    /// `Implements` edges from a type to the derived trait
+    pub derived: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum RelationshipType {
-    #[allow(dead_code)]
     Calls,
-    #[allow(dead_code)]
     Imports,
     Implements,
     Contains,
+    AssociatedWith, // Method or associated function and its self type
+    Uses,           // Function takes or returns a type
+    References,     // Type holds another type behind a reference or pointer
+    DependsOn,      // Type owns a value of another type in a field or variant
+    Extends,        // Trait and one of its supertraits
+    BoundedBy,      // Item constrains a generic parameter with a trait
+    Spawns,         // Function or task starts a task or thread running the target
+    ReturnsError,   // Function returns a `Result` with the target as its error type
+    ConvertsFrom,   // Type has an `impl From<Target>`, so `?` turns the target into it
 }
 
-pub fn parse_file(file_path: &str, content: &str) -> Result<(Vec<CodeElement>, Vec<Relationship>), String> {
+/// Everything extracted from one source file.
+#[derive(Debug, Default)]
+pub struct ParsedFile {
+    pub elements: Vec<CodeElement>,
+    pub relationships: Vec<Relationship>,
+    /// `mod foo;` declarations, for the project to follow into other files
+    pub module_decls: Vec<ModuleDecl>,
+    /// Items that couldn't be parsed and were left out
+    pub errors: Vec<SyntaxError>,
+}
+
+/// A syntax error, located at the token where parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    /// 1-based, like `SourceSpan`
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl From<&syn::Error> for SyntaxError {
+    fn from(error: &syn::Error) -> Self {
+        let start = error.span().start();
+        Self {
+            line: start.line,
+            column: start.column + 1,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A `mod foo;` declaration whose contents live in another file.
+#[derive(Debug, Clone)]
+pub struct ModuleDecl {
+    /// Module the declaration appears in. This is deeper than the file's own
+    /// module when the declaration sits inside an inline `mod a { ... }`.
+    pub parent_path: String,
+    pub name: String,
+    /// Value of a `#[path = "..."]` attribute on the declaration
+    pub path_attr: Option<String>,
+    /// Visibility and attributes of the declaration, which belong to the
+    /// module the file defines
+    pub details: ElementDetails,
+    /// Whether the declaration's `cfg` attributes hold, or make it a test
+    /// module; the module isn't loaded otherwise
+    pub cfg_active: bool,
+}
+
+/// Parses one source file.
+///
+/// `module_path` is the path of the module the file defines (`crate`,
+/// `crate::parser`, ...); element IDs are built from it so that they stay
+/// the same across re-parses and never depend on the order of items.
+///
+/// Items whose `#[cfg]` doesn't hold under `cfg` are left out, so that
+/// platform-specific alternatives don't produce duplicate IDs.
+///
+/// Items with syntax errors are skipped and reported in `errors`; the rest of
+/// the file is still parsed. Only a file that can't be tokenized fails.
+pub fn parse_file(file_path: &str, module_path: &str, content: &str, cfg: &CfgSet) -> Result<ParsedFile, SyntaxError> {
     let mut elements = Vec::new();
     let mut relationships = Vec::new();
+    let mut module_decls = Vec::new();
     
     // Parse the file using syn
-    let file = syn_parse_file(content).map_err(|e| e.to_string())?;
+    let (mut file, errors) = recover::parse_file_recovering(content)?;
+    cfg::strip_inactive(&mut file, cfg);
+    
+    // The file itself is a module; imports and nested items hang off it
+    elements.push(CodeElement {
+        id: module_path.to_string(),
+        name: module_path.rsplit("::").next().unwrap_or(module_path).to_string(),
+        element_type: ElementType::Module,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(&file),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&syn::Visibility::Inherited, &file.attrs, &syn::Generics::default()),
+    });
     
     // Process items in the file
-    process_items(file_path, &file.items, &mut elements, &mut relationships);
+    process_items(file_path, module_path, &file.items, cfg, &mut elements, &mut relationships, &mut module_decls);
     
-    Ok((elements, relationships))
+    for element in &mut elements {
+        if let Some(metrics) = &mut element.metrics {
+            metrics.lines = metrics::count_code_lines(content, element.span);
+        }
+    }
+    
+    Ok(ParsedFile {
+        elements,
+        relationships,
+        module_decls,
+        errors,
+    })
 }
 
-fn process_items(file_path: &str, items: &[Item], elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+/// Derives the module path of a file from its location in the crate, e.g.
+/// `src/parser/calls.rs` becomes `crate::parser::calls` and `src/lib.rs`
+/// becomes `crate`. Used for files that aren't reachable through `mod`
+/// declarations, where the real module path is unknown.
+pub fn module_path_for_file(file_path: &str) -> String {
+    let path = file_path.trim_start_matches('/');
+    let path = path.strip_prefix("src/").unwrap_or(path);
+    let path = path.strip_suffix(".rs").unwrap_or(path);
+    
+    let mut module_path = vec!["crate"];
+    module_path.extend(path.split('/').filter(|segment| !segment.is_empty()));
+    if matches!(module_path.last(), Some(&"mod") | Some(&"lib") | Some(&"main")) {
+        module_path.pop();
+    }
+    module_path.join("::")
+}
+
+fn process_items<'a>(
+    file_path: &str,
+    module_path: &str,
+    items: impl IntoIterator<Item = &'a Item>,
+    cfg: &CfgSet,
+    elements: &mut Vec<CodeElement>,
+    relationships: &mut Vec<Relationship>,
+    module_decls: &mut Vec<ModuleDecl>,
+) {
     for item in items {
         match item {
             Item::Use(use_item) => {
-                process_use_statement(file_path, use_item, relationships);
+                let anchor = enclosing_module(module_path, elements);
+                process_use_statement(module_path, anchor, use_item, relationships);
             },
             Item::Fn(func) => {
-                process_function(file_path, func, elements, relationships);
+                process_function(file_path, module_path, func, cfg, elements, relationships);
             },
             Item::Mod(module) => {
+                let mod_name = module.ident.to_string();
+                
                 if let Some(content) = &module.content {
-                    let mod_name = module.ident.to_string();
-                    let mod_id = format!("{}::{}", file_path, mod_name);
+                    let mod_id = format!("{}::{}", module_path, mod_name);
                     
                     elements.push(CodeElement {
                         id: mod_id.clone(),
                         name: mod_name,
                         element_type: ElementType::Module,
                         file_path: file_path.to_string(),
-                        start_line: 0,
-                        end_line: 0,
+                        module_path: mod_id.clone(),
+                        span: SourceSpan::of(module),
+                        receiver: None,
+                        shape: None,
+                        metrics: None,
+                        details: ElementDetails::of(&module.vis, &module.attrs, &syn::Generics::default()),
                     });
                     
-                    process_items(file_path, &content.1, elements, relationships);
+                    relationships.push(Relationship {
+                        source_id: module_path.to_string(),
+                        target_id: mod_id.clone(),
+                        relationship_type: RelationshipType::Contains,
+                        derived: false,
+                    });
+                    
+                    process_items(file_path, &mod_id, &content.1, cfg, elements, relationships, module_decls);
+                } else {
+                    // File-backed module; the project locates and parses the file
+                    module_decls.push(ModuleDecl {
+                        parent_path: module_path.to_string(),
+                        name: mod_name,
+                        path_attr: path_attribute(&module.attrs),
+                        details: ElementDetails::of(&module.vis, &module.attrs, &syn::Generics::default()),
+                        cfg_active: cfg.loads_module(&module.attrs),
+                    });
                 }
             },
             Item::Struct(struct_item) => {
-                process_struct(file_path, struct_item, elements, relationships);
+                process_struct(file_path, module_path, struct_item, elements, relationships);
             },
             Item::Enum(enum_item) => {
-                process_enum(file_path, enum_item, elements, relationships);
+                process_enum(file_path, module_path, enum_item, elements, relationships);
             },
             Item::Trait(trait_item) => {
-                process_trait(file_path, trait_item, elements, relationships);
+                process_trait(file_path, module_path, trait_item, cfg, elements, relationships);
             },
             Item::Impl(impl_item) => {
-                process_impl(file_path, impl_item, elements, relationships);
+                process_impl(file_path, module_path, impl_item, cfg, elements, relationships);
+            },
+            Item::Const(const_item) => {
+                process_const(file_path, module_path, const_item, elements, relationships);
+            },
+            Item::Static(static_item) => {
+                process_static(file_path, module_path, static_item, elements, relationships);
+            },
+            Item::Type(type_item) => {
+                process_type_alias(file_path, module_path, type_item, elements, relationships);
+            },
+            Item::Union(union_item) => {
+                process_union(file_path, module_path, union_item, elements, relationships);
+            },
+            Item::Macro(macro_item) => {
+                process_macro(file_path, module_path, macro_item, elements);
+            },
+            Item::ForeignMod(foreign_mod) => {
+                process_foreign_mod(file_path, module_path, foreign_mod, elements, relationships);
             },
             _ => {}
         }
     }
 }
 
-fn process_use_statement(file_path: &str, use_item: &ItemUse, relationships: &mut Vec<Relationship>) {
-    // Extract the imported path and create an import relationship
-    let imported_path = extract_use_path(&use_item.tree);
-    if !imported_path.is_empty() {
-        let source_id = format!("{}::use::{}", file_path, imported_path);
-        let target_id = imported_path;
-        
+/// Emits an `Imports` edge from the module to every item a `use` brings in.
+/// A renamed import is written `path as Alias` until paths are resolved, so
+/// the project knows which name it binds. `self` and `super` are expanded
+/// from `anchor`, the module the `use` sits in.
+fn process_use_statement(module_path: &str, anchor: &str, use_item: &ItemUse, relationships: &mut Vec<Relationship>) {
+    for (imported_path, local_name) in imports::expand_use_tree(anchor, &use_item.tree) {
+        let target_id = if imported_path.rsplit("::").next() == Some(local_name.as_str()) {
+            imported_path
+        } else {
+            format!("{} as {}", imported_path, local_name)
+        };
         relationships.push(Relationship {
-            source_id,
+            source_id: module_path.to_string(),
             target_id,
             relationship_type: RelationshipType::Imports,
+            derived: false,
         });
     }
 }
 
-fn extract_use_path(tree: &syn::UseTree) -> String {
-    match tree {
-        syn::UseTree::Path(path) => {
-            format!("{}::{}", path.ident, extract_use_path(&path.tree))
-        },
-        syn::UseTree::Name(name) => name.ident.to_string(),
-        syn::UseTree::Rename(rename) => rename.ident.to_string(),
-        syn::UseTree::Glob(_) => "*".to_string(),
-        syn::UseTree::Group(group) => {
-            group.items.iter()
-                .map(|item| extract_use_path(item))
-                .collect::<Vec<_>>()
-                .join(", ")
-        },
+/// The module `self` and `super` are relative to inside `scope`: the scope
+/// itself, or for a function body, the module the function is declared in.
+fn enclosing_module<'a>(scope: &'a str, elements: &[CodeElement]) -> &'a str {
+    let mut module = scope;
+    while !elements.iter().any(|e| e.element_type == ElementType::Module && e.id == module) {
+        match module.rsplit_once("::") {
+            Some((parent, _)) => module = parent,
+            None => return scope,
+        }
     }
+    module
 }
 
-fn process_function(file_path: &str, func: &ItemFn, elements: &mut Vec<CodeElement>, _relationships: &mut Vec<Relationship>) {
+fn process_function(file_path: &str, module_path: &str, func: &ItemFn, cfg: &CfgSet, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
     let fn_name = func.sig.ident.to_string();
-    let fn_id = format!("{}::{}", file_path, fn_name);
+    let fn_id = format!("{}::{}", module_path, fn_name);
     
     elements.push(CodeElement {
         id: fn_id.clone(),
         name: fn_name,
         element_type: ElementType::Function,
         file_path: file_path.to_string(),
-        start_line: 0, // TODO: Get actual line numbers
-        end_line: 0,
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(func),
+        receiver: None,
+        shape: None,
+        metrics: Some(FunctionMetrics::of(&func.sig, &func.block)),
+        details: ElementDetails::of(&func.vis, &func.attrs, &func.sig.generics)
+            .with_signature(&func.sig)
+            .with_body(&func.sig, &func.block),
     });
     
-    // Add basic function call analysis (simplified for this example)
-    // In a real implementation, you'd need much more sophisticated analysis
+    let generic_params = type_refs::generic_param_names(&func.sig.generics);
+    process_signature_types(&fn_id, &func.sig, &generic_params, None, relationships);
+    process_bounds(&fn_id, &func.sig.generics, relationships);
+    process_calls(&fn_id, calls::collect_calls(&func.block, None), relationships);
+    process_spawns(file_path, module_path, &fn_id, tasks::collect_spawns(&func.block, None), None, elements, relationships);
+    process_body_items(file_path, &fn_id, &func.block, cfg, elements, relationships);
+}
+
+/// Records the items declared inside a function body: nested functions, local
+/// types and their impls. They can't be named from outside the function, so
+/// they are scoped under its ID as if it were a module, and the function gets
+/// a `Contains` edge to each named one.
+fn process_body_items(
+    file_path: &str,
+    function_id: &str,
+    body: &syn::Block,
+    cfg: &CfgSet,
+    elements: &mut Vec<CodeElement>,
+    relationships: &mut Vec<Relationship>,
+) {
+    let mut collector = BodyItems::default();
+    collector.visit_block(body);
+    if collector.items.is_empty() {
+        return;
+    }
+    
+    let first_element = elements.len();
+    // A block can't declare a file-backed module, so there are none to load
+    process_items(file_path, function_id, collector.items.iter().copied(), cfg, elements, relationships, &mut Vec::new());
+    
+    for item in collector.items {
+        let ident = match item {
+            Item::Fn(item) => &item.sig.ident,
+            Item::Struct(item) => &item.ident,
+            Item::Enum(item) => &item.ident,
+            Item::Union(item) => &item.ident,
+            Item::Trait(item) => &item.ident,
+            Item::Type(item) => &item.ident,
+            Item::Const(item) => &item.ident,
+            Item::Static(item) => &item.ident,
+            _ => continue,
+        };
+        let item_id = format!("{}::{}", function_id, ident);
+        if elements[first_element..].iter().any(|e| e.id == item_id) {
+            relationships.push(Relationship {
+                source_id: function_id.to_string(),
+                target_id: item_id,
+                relationship_type: RelationshipType::Contains,
+                derived: false,
+            });
+        }
+    }
+}
+
+/// Collects the items of a function body, in nested blocks too, but not the
+/// items inside those items, which `process_items` gets to on its own.
+#[derive(Default)]
+struct BodyItems<'ast> {
+    items: Vec<&'ast Item>,
+}
+
+impl<'ast> Visit<'ast> for BodyItems<'ast> {
+    fn visit_item(&mut self, item: &'ast Item) {
+        self.items.push(item);
+    }
+}
+
+/// Emits a `Calls` relationship for every callee, and a `Uses` relationship
+/// for every struct the body builds that the signature doesn't already use.
+///
+/// Targets are left as the path seen in the source; the project resolves them
+/// to element IDs once every file has been parsed.
+fn process_calls(source_id: &str, calls: calls::BodyCalls, relationships: &mut Vec<Relationship>) {
+    for callee in calls.callees {
+        relationships.push(Relationship {
+            source_id: source_id.to_string(),
+            target_id: callee,
+            relationship_type: RelationshipType::Calls,
+            derived: false,
+        });
+    }
+    for constructed in calls.constructed {
+        // The edges of the signature were emitted just before
+        let already_used = relationships.iter()
+            .rev()
+            .take_while(|r| r.source_id == source_id)
+            .any(|r| r.relationship_type == RelationshipType::Uses && r.target_id == constructed);
+        if !already_used {
+            relationships.push(Relationship {
+                source_id: source_id.to_string(),
+                target_id: constructed,
+                relationship_type: RelationshipType::Uses,
+                derived: false,
+            });
+        }
+    }
+}
+
+/// Emits a `Spawns` relationship for every task started by `source_id`.
+///
+/// A closure or async block spawned in place becomes a `Task` element that
+/// makes the calls and spawns of its own body. A spawned function is left as
+/// written, like a callee, for the project to resolve.
+fn process_spawns(
+    file_path: &str,
+    module_path: &str,
+    source_id: &str,
+    spawns: Vec<tasks::Spawn>,
+    self_type: Option<&str>,
+    elements: &mut Vec<CodeElement>,
+    relationships: &mut Vec<Relationship>,
+) {
+    for spawn in spawns {
+        let target_id = match spawn.task {
+            tasks::SpawnedTask::Function(function) => function,
+            tasks::SpawnedTask::Body(body) => {
+                // Number the tasks of one function like impl blocks of one type
+                let base_id = format!("{}::<task>", source_id);
+                let mut task_id = base_id.clone();
+                let mut occurrence = 1;
+                while elements.iter().any(|e| e.id == task_id) {
+                    occurrence += 1;
+                    task_id = format!("{}#{}", base_id, occurrence);
+                }
+                
+                let (code, is_async, placeholder) = match body {
+                    syn::Expr::Closure(closure) => (
+                        &*closure.body,
+                        closure.asyncness.is_some() || matches!(&*closure.body, syn::Expr::Async(_)),
+                        "|| …",
+                    ),
+                    _ => (body, true, "async …"),
+                };
+                elements.push(CodeElement {
+                    id: task_id.clone(),
+                    name: format!("{}({})", spawn.spawner.trim_start_matches('.'), placeholder),
+                    element_type: ElementType::Task,
+                    file_path: file_path.to_string(),
+                    module_path: module_path.to_string(),
+                    span: SourceSpan::of(body),
+                    receiver: None,
+                    shape: None,
+                    metrics: None,
+                    details: ElementDetails {
+                        is_async,
+                        await_points: tasks::count_awaits_in_expr(code),
+                        ..ElementDetails::default()
+                    },
+                });
+                
+                process_calls(&task_id, calls::collect_calls_in_expr(code, self_type), relationships);
+                let nested = tasks::collect_spawns_in_expr(code, self_type);
+                process_spawns(file_path, module_path, &task_id, nested, self_type, elements, relationships);
+                task_id
+            },
+        };
+        
+        relationships.push(Relationship {
+            source_id: source_id.to_string(),
+            target_id,
+            relationship_type: RelationshipType::Spawns,
+            derived: false,
+        });
+    }
+}
+
+/// Emits `Uses` relationships for the types of a function's parameters and
+/// return value, and a `ReturnsError` relationship for the error type of a
+/// returned `Result`.
+fn process_signature_types(source_id: &str, sig: &syn::Signature, generic_params: &[String], self_type: Option<&str>, relationships: &mut Vec<Relationship>) {
+    let params = sig.inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(pat_type) => Some(&*pat_type.ty),
+        syn::FnArg::Receiver(_) => None,
+    });
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty) => Some(&**ty),
+        syn::ReturnType::Default => None,
+    };
+    
+    process_type_usage(source_id, params.chain(output), generic_params, self_type, RelationshipType::Uses, relationships);
+    if let Some(error_type) = errors::ErrorType::of_output(&sig.output) {
+        process_error_type(source_id, error_type, generic_params, self_type, relationships);
+    }
+}
+
+/// Emits a `ReturnsError` relationship to a named error type, or to the
+/// `Result` alias for the project to look through.
+fn process_error_type(source_id: &str, error_type: errors::ErrorType, generic_params: &[String], self_type: Option<&str>, relationships: &mut Vec<Relationship>) {
+    if let Some(target_id) = error_type.path(generic_params, self_type) {
+        relationships.push(Relationship {
+            source_id: source_id.to_string(),
+            target_id,
+            relationship_type: RelationshipType::ReturnsError,
+            derived: false,
+        });
+    }
+}
+
+/// Emits a `kind` relationship from `source_id` to each type named in
+/// `types`, once per type.
+///
+/// For `DependsOn`, types only held through a reference or pointer get a
+/// weaker `References` edge instead, since the owner doesn't contain them.
+/// Targets are the paths as written; the project resolves them.
+fn process_type_usage<'a>(
+    source_id: &str,
+    types: impl Iterator<Item = &'a syn::Type>,
+    generic_params: &[String],
+    self_type: Option<&str>,
+    kind: RelationshipType,
+    relationships: &mut Vec<Relationship>,
+) {
+    let mut seen: Vec<(String, RelationshipType)> = Vec::new();
+    for ty in types {
+        for type_ref in type_refs::collect_type_refs(ty, generic_params, self_type) {
+            let relationship_type = if kind == RelationshipType::DependsOn && type_ref.behind_reference {
+                RelationshipType::References
+            } else {
+                kind
+            };
+            if seen.iter().any(|(path, rt)| *path == type_ref.path && *rt == relationship_type) {
+                continue;
+            }
+            seen.push((type_ref.path.clone(), relationship_type));
+            
+            relationships.push(Relationship {
+                source_id: source_id.to_string(),
+                target_id: type_ref.path,
+                relationship_type,
+                derived: false,
+            });
+        }
+    }
+}
+
+/// Emits a `BoundedBy` relationship from `source_id` to every trait its
+/// generic parameters are constrained with, inline (`<T: Trait>`) or in a
+/// `where` clause. Supertrait-style `Self` bounds are left to the caller.
+fn process_bounds(source_id: &str, generics: &syn::Generics, relationships: &mut Vec<Relationship>) {
+    let inline_bounds = generics.type_params().flat_map(|param| param.bounds.iter());
+    let where_bounds = generics.where_clause.iter()
+        .flat_map(|clause| clause.predicates.iter())
+        .filter_map(|predicate| match predicate {
+            syn::WherePredicate::Type(predicate) if !is_self_type(&predicate.bounded_ty) => Some(&predicate.bounds),
+            _ => None,
+        })
+        .flatten();
+    
+    let mut seen: Vec<String> = Vec::new();
+    for bound in inline_bounds.chain(where_bounds) {
+        let Some(trait_path) = trait_bound_path(bound) else {
+            continue;
+        };
+        if seen.contains(&trait_path) {
+            continue;
+        }
+        seen.push(trait_path.clone());
+        
+        relationships.push(Relationship {
+            source_id: source_id.to_string(),
+            target_id: trait_path,
+            relationship_type: RelationshipType::BoundedBy,
+            derived: false,
+        });
+    }
+}
+
+/// Emits a derived `Implements` edge from a type to every trait its
+/// `#[derive]` attributes implement.
+fn process_derives(type_id: &str, attrs: &[syn::Attribute], relationships: &mut Vec<Relationship>) {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let Ok(paths) = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated) else {
+            continue;
+        };
+        for path in paths {
+            relationships.push(Relationship {
+                source_id: type_id.to_string(),
+                target_id: std_trait_path(plain_path(&path)),
+                relationship_type: RelationshipType::Implements,
+                derived: true,
+            });
+        }
+    }
+}
+
+/// Path of the trait in a bound, without generic arguments. Lifetimes and
+/// `?Sized`-style relaxed bounds don't name a trait to depend on.
+fn trait_bound_path(bound: &syn::TypeParamBound) -> Option<String> {
+    match bound {
+        syn::TypeParamBound::Trait(trait_bound) if matches!(trait_bound.modifier, syn::TraitBoundModifier::None) => {
+            Some(std_trait_path(plain_path(&trait_bound.path)))
+        },
+        _ => None,
+    }
+}
+
+/// Points the common standard library traits at their full path, so that the
+/// same trait gets one target however it is written (`Debug`, `fmt::Debug`,
+/// `core::fmt::Debug`); other paths are kept as written.
+fn std_trait_path(trait_path: String) -> String {
+    let name = trait_path.strip_prefix("core::").or_else(|| trait_path.strip_prefix("std::")).unwrap_or(&trait_path);
+    match name {
+        "Debug" | "fmt::Debug" => "std::fmt::Debug".to_string(),
+        "fmt::Display" => "std::fmt::Display".to_string(),
+        "Clone" | "clone::Clone" => "std::clone::Clone".to_string(),
+        "Copy" | "marker::Copy" => "std::marker::Copy".to_string(),
+        "Send" | "marker::Send" => "std::marker::Send".to_string(),
+        "Sync" | "marker::Sync" => "std::marker::Sync".to_string(),
+        "Default" | "default::Default" => "std::default::Default".to_string(),
+        "Hash" | "hash::Hash" => "std::hash::Hash".to_string(),
+        "PartialEq" | "Eq" | "PartialOrd" | "Ord" => format!("std::cmp::{}", name),
+        "cmp::PartialEq" | "cmp::Eq" | "cmp::PartialOrd" | "cmp::Ord" => format!("std::{}", name),
+        "Serialize" | "Deserialize" => format!("serde::{}", name),
+        _ => trait_path,
+    }
 }
 
-fn process_struct(file_path: &str, struct_item: &ItemStruct, elements: &mut Vec<CodeElement>, _relationships: &mut Vec<Relationship>) {
+fn is_self_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident("Self"))
+}
+
+fn process_struct(file_path: &str, module_path: &str, struct_item: &ItemStruct, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
     let struct_name = struct_item.ident.to_string();
-    let struct_id = format!("{}::{}", file_path, struct_name);
+    let struct_id = format!("{}::{}", module_path, struct_name);
     
     elements.push(CodeElement {
         id: struct_id.clone(),
-        name: struct_name,
+        name: struct_name.clone(),
         element_type: ElementType::Struct,
         file_path: file_path.to_string(),
-        start_line: 0, // TODO: Get actual line numbers
-        end_line: 0,
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(struct_item),
+        receiver: None,
+        shape: Some(TypeShape::Struct(Fields::of(&struct_item.fields))),
+        metrics: None,
+        details: ElementDetails::of(&struct_item.vis, &struct_item.attrs, &struct_item.generics),
     });
     
-    // Analysis of struct fields and potential relationships could be added here
+    // Field types are the struct's data dependencies
+    let generic_params = type_refs::generic_param_names(&struct_item.generics);
+    let field_types = struct_item.fields.iter().map(|field| &field.ty);
+    process_type_usage(&struct_id, field_types, &generic_params, Some(&struct_name), RelationshipType::DependsOn, relationships);
+    process_bounds(&struct_id, &struct_item.generics, relationships);
+    process_derives(&struct_id, &struct_item.attrs, relationships);
 }
 
-fn process_enum(file_path: &str, enum_item: &ItemEnum, elements: &mut Vec<CodeElement>, _relationships: &mut Vec<Relationship>) {
+fn process_enum(file_path: &str, module_path: &str, enum_item: &ItemEnum, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
     let enum_name = enum_item.ident.to_string();
-    let enum_id = format!("{}::{}", file_path, enum_name);
+    let enum_id = format!("{}::{}", module_path, enum_name);
     
     elements.push(CodeElement {
         id: enum_id.clone(),
-        name: enum_name,
+        name: enum_name.clone(),
         element_type: ElementType::Enum,
         file_path: file_path.to_string(),
-        start_line: 0, // TODO: Get actual line numbers
-        end_line: 0,
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(enum_item),
+        receiver: None,
+        shape: Some(TypeShape::Enum(enum_item.variants.iter().map(Variant::of).collect())),
+        metrics: None,
+        details: ElementDetails::of(&enum_item.vis, &enum_item.attrs, &enum_item.generics),
     });
     
-    // Analysis of enum variants could be added here
+    // Variant payloads are the enum's data dependencies
+    let generic_params = type_refs::generic_param_names(&enum_item.generics);
+    let payload_types = enum_item.variants.iter()
+        .flat_map(|variant| variant.fields.iter())
+        .map(|field| &field.ty);
+    process_type_usage(&enum_id, payload_types, &generic_params, Some(&enum_name), RelationshipType::DependsOn, relationships);
+    process_bounds(&enum_id, &enum_item.generics, relationships);
+    process_derives(&enum_id, &enum_item.attrs, relationships);
 }
 
-fn process_trait(file_path: &str, trait_item: &ItemTrait, elements: &mut Vec<CodeElement>, _relationships: &mut Vec<Relationship>) {
+fn process_trait(file_path: &str, module_path: &str, trait_item: &ItemTrait, cfg: &CfgSet, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
     let trait_name = trait_item.ident.to_string();
-    let trait_id = format!("{}::{}", file_path, trait_name);
+    let trait_id = format!("{}::{}", module_path, trait_name);
     
     elements.push(CodeElement {
         id: trait_id.clone(),
-        name: trait_name,
+        name: trait_name.clone(),
         element_type: ElementType::Trait,
         file_path: file_path.to_string(),
-        start_line: 0, // TODO: Get actual line numbers
-        end_line: 0,
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(trait_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&trait_item.vis, &trait_item.attrs, &trait_item.generics)
+            .with_unsafe(trait_item.unsafety.is_some()),
     });
     
-    // Analysis of trait methods could be added here
+    // `trait A: B + C` and `where Self: B` both make `B` a supertrait
+    let where_self_bounds = trait_item.generics.where_clause.iter()
+        .flat_map(|clause| clause.predicates.iter())
+        .filter_map(|predicate| match predicate {
+            syn::WherePredicate::Type(predicate) if is_self_type(&predicate.bounded_ty) => Some(&predicate.bounds),
+            _ => None,
+        })
+        .flatten();
+    let mut supertraits: Vec<String> = Vec::new();
+    for bound in trait_item.supertraits.iter().chain(where_self_bounds) {
+        if let Some(supertrait) = trait_bound_path(bound) {
+            if !supertraits.contains(&supertrait) {
+                supertraits.push(supertrait);
+            }
+        }
+    }
+    for supertrait in supertraits {
+        relationships.push(Relationship {
+            source_id: trait_id.clone(),
+            target_id: supertrait,
+            relationship_type: RelationshipType::Extends,
+            derived: false,
+        });
+    }
+    process_bounds(&trait_id, &trait_item.generics, relationships);
+    
+    let generic_params = type_refs::generic_param_names(&trait_item.generics);
+    let owner = MethodOwner {
+        file_path,
+        module_path,
+        cfg,
+        parent_id: &trait_id,
+        self_type_id: None,
+        self_type: Some(&trait_name),
+        generic_params: &generic_params,
+        item_visibility: Some(Visibility::of(&trait_item.vis)),
+    };
+    
+    // Trait methods; only default methods have a body to analyze
+    for item in &trait_item.items {
+        if let syn::TraitItem::Fn(method) = item {
+            let method_name = method.sig.ident.to_string();
+            let method_id = format!("{}::{}", trait_id, method_name);
+            
+            let details = ElementDetails::of(&syn::Visibility::Inherited, &method.attrs, &method.sig.generics);
+            process_method(&owner, &method_id, &method.sig, method.default.as_ref(), SourceSpan::of(method), details, elements, relationships);
+        } else if let syn::TraitItem::Const(const_item) = item {
+            let const_id = format!("{}::{}", trait_id, const_item.ident);
+            let details = ElementDetails::of(&syn::Visibility::Inherited, &const_item.attrs, &const_item.generics);
+            process_associated_const(&owner, &const_id, &const_item.ty, SourceSpan::of(const_item), details, elements, relationships);
+        }
+    }
 }
 
-fn process_impl(file_path: &str, impl_item: &ItemImpl, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
-    // For impl blocks, we may want to create a unique ID
-    // We'll use the type name it's implementing for
-    let impl_id = format!("{}::impl{}", file_path, elements.len());
+fn process_impl(file_path: &str, module_path: &str, impl_item: &ItemImpl, cfg: &CfgSet, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let self_ty = &impl_item.self_ty;
+    let type_name = extract_type_name(self_ty);
+    let generic_params = type_refs::generic_param_names(&impl_item.generics);
     
-    // Determine if this is a trait implementation or inherent implementation
-    let impl_name = if let Some(trait_path) = &impl_item.trait_ {
+    // `impl<T: Bound> Trait for T` implements the trait for every type that
+    // meets the bounds rather than for one type of the project
+    let is_blanket = impl_item.trait_.is_some() && generic_params.contains(&type_name);
+    
+    // Methods are qualified by the self type for inherent impls and by the
+    // `<Type as Trait>` pair for trait impls, mirroring how Rust names them
+    let (impl_name, impl_path) = if let Some(trait_path) = &impl_item.trait_ {
         // This is a trait implementation (impl TraitName for Type)
         let trait_name = trait_path.1.segments.last()
             .map(|s| s.ident.to_string())
             .unwrap_or_else(|| "Unknown".to_string());
+        let trait_ref = tokens_to_string(&trait_path.1);
+        let self_ref = tokens_to_string(self_ty);
         
-        // Also try to find the implemented type
-        let self_ty = &impl_item.self_ty;
-        // Extract type name without Debug formatting
-        let type_name = extract_type_name(self_ty);
-        
-        // Create a relationship between the impl and the struct/type it's implementing for
-        // This is a simplification - proper path resolution would be better
-        let target_id = format!("{}::{}", file_path, type_name.replace(' ', ""));
-        
-        relationships.push(Relationship {
-            source_id: impl_id.clone(),
-            target_id,
-            relationship_type: RelationshipType::Implements,
-        });
-        
-        format!("impl {} for {}", trait_name, type_name)
+        (
+            if is_blanket {
+                format!("impl<{}> {} for {}", type_name, trait_name, self_ref)
+            } else {
+                format!("impl {} for {}", trait_name, type_name)
+            },
+            format!("{}::<{} as {}>", module_path, self_ref, trait_ref),
+        )
     } else {
         // This is an inherent implementation (impl Type)
-        let self_ty = &impl_item.self_ty;
-        let type_name = extract_type_name(self_ty);
-        
-        // Try to create a relationship with the struct/type
-        let target_id = format!("{}::{}", file_path, type_name.replace(' ', ""));
-        
-        relationships.push(Relationship {
+        let self_name = inherent_self_name(self_ty, &type_name, &generic_params);
+        (
+            format!("impl {}", self_name),
+            format!("{}::{}", module_path, self_name),
+        )
+    };
+    
+    // A type may have several inherent impl blocks in one module; number the
+    // extra ones so each block keeps an ID of its own
+    let base_impl_id = match &impl_item.trait_ {
+        Some(trait_path) => format!(
+            "{}::<impl {} for {}>",
+            module_path,
+            tokens_to_string(&trait_path.1),
+            tokens_to_string(self_ty)
+        ),
+        None => format!("{}::<{}>", module_path, impl_name),
+    };
+    let mut impl_id = base_impl_id.clone();
+    let mut occurrence = 1;
+    while elements.iter().any(|e| e.id == impl_id) {
+        occurrence += 1;
+        impl_id = format!("{}#{}", base_impl_id, occurrence);
+    }
+    
+    let target_id = format!("{}::{}", module_path, type_name);
+    match &impl_item.trait_ {
+        // A blanket impl has no self type to point at, so it is linked to the
+        // trait it provides instead
+        Some(trait_path) if is_blanket => relationships.push(Relationship {
             source_id: impl_id.clone(),
-            target_id,
-            relationship_type: RelationshipType::Contains,
+            target_id: std_trait_path(plain_path(&trait_path.1)),
+            relationship_type: RelationshipType::Implements,
+            derived: false,
+        }),
+        _ => relationships.push(Relationship {
+            source_id: impl_id.clone(),
+            target_id: target_id.clone(),
+            relationship_type: if impl_item.trait_.is_some() {
+                RelationshipType::Implements
+            } else {
+                RelationshipType::Contains
+            },
+            derived: false,
+        }),
+    }
+    // `impl From<A> for B` is what lets `?` turn an `A` into a `B`
+    let converted = impl_item.trait_.as_ref()
+        .filter(|_| !is_blanket)
+        .and_then(|(_, trait_path, _)| errors::from_source(trait_path))
+        .and_then(|source| errors::type_path(source, &generic_params, Some(&type_name)));
+    if let Some(source) = converted {
+        relationships.push(Relationship {
+            source_id: target_id.clone(),
+            target_id: source,
+            relationship_type: RelationshipType::ConvertsFrom,
+            derived: false,
         });
-        
-        format!("impl {}", type_name)
-    };
+    }
+    process_bounds(&impl_id, &impl_item.generics, relationships);
     
     elements.push(CodeElement {
-        id: impl_id,
+        id: impl_id.clone(),
         name: impl_name,
         element_type: ElementType::Impl,
         file_path: file_path.to_string(),
-        start_line: 0, // TODO: Get actual line numbers
-        end_line: 0,
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(impl_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&syn::Visibility::Inherited, &impl_item.attrs, &impl_item.generics)
+            .with_unsafe(impl_item.unsafety.is_some()),
     });
     
+    let owner = MethodOwner {
+        file_path,
+        module_path,
+        cfg,
+        parent_id: &impl_id,
+        self_type_id: (!is_blanket).then_some(target_id.as_str()),
+        self_type: (!is_blanket).then_some(type_name.as_str()),
+        generic_params: &generic_params,
+        // Trait impl items are as visible as the trait itself
+        item_visibility: impl_item.trait_.as_ref().map(|_| Visibility::Public),
+    };
+    
     // Process methods within the impl block
     for item in &impl_item.items {
         match item {
             syn::ImplItem::Fn(method) => {
                 let method_name = method.sig.ident.to_string();
-                let method_id = format!("{}::{}", file_path, method_name);
+                let method_id = format!("{}::{}", impl_path, method_name);
                 
-                elements.push(CodeElement {
-                    id: method_id.clone(),
-                    name: method_name,
-                    element_type: ElementType::Function,
-                    file_path: file_path.to_string(),
-                    start_line: 0,
-                    end_line: 0,
-                });
+                let details = ElementDetails::of(&method.vis, &method.attrs, &method.sig.generics);
+                process_method(&owner, &method_id, &method.sig, Some(&method.block), SourceSpan::of(method), details, elements, relationships);
+            },
+            syn::ImplItem::Const(const_item) => {
+                let const_id = format!("{}::{}", impl_path, const_item.ident);
+                let details = ElementDetails::of(&const_item.vis, &const_item.attrs, &const_item.generics);
+                process_associated_const(&owner, &const_id, &const_item.ty, SourceSpan::of(const_item), details, elements, relationships);
             },
             // Other impl items can be processed here
             _ => {}
@@ -267,6 +1284,384 @@ fn process_impl(file_path: &str, impl_item: &ItemImpl, elements: &mut Vec<CodeEl
     }
 }
 
+/// Name an inherent impl's items are qualified by: the type's name, or the
+/// type with its arguments when the impl is for one instantiation only, as
+/// `impl Wrapper<u8>` and `impl Wrapper<u16>` may both declare a `new`.
+fn inherent_self_name(self_ty: &syn::Type, type_name: &str, generic_params: &[String]) -> String {
+    let syn::Type::Path(type_path) = self_ty else {
+        return type_name.to_string();
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return type_name.to_string();
+    };
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return type_name.to_string();
+    };
+    let is_generic = arguments.args.iter().all(|argument| match argument {
+        syn::GenericArgument::Lifetime(_) => true,
+        syn::GenericArgument::Type(syn::Type::Path(param)) => {
+            param.qself.is_none() && param.path.get_ident().is_some_and(|ident| generic_params.iter().any(|p| ident == p))
+        },
+        _ => false,
+    });
+    if is_generic {
+        type_name.to_string()
+    } else {
+        tokens_to_string(segment)
+    }
+}
+
+/// The impl block or trait a method is declared in.
+struct MethodOwner<'a> {
+    file_path: &'a str,
+    module_path: &'a str,
+    cfg: &'a CfgSet,
+    /// ID of the impl block or trait itself
+    parent_id: &'a str,
+    /// ID of the self type, for impls
+    self_type_id: Option<&'a str>,
+    /// Name `Self` stands for in signatures; unset for blanket impls, where
+    /// it is a type parameter
+    self_type: Option<&'a str>,
+    /// Type parameters of the impl or trait, in scope in every method
+    generic_params: &'a [String],
+    /// Visibility of every item, for traits and trait impls where items
+    /// can't declare their own
+    item_visibility: Option<Visibility>,
+}
+
+/// Records a function declared in an impl block or trait.
+///
+/// The enclosing impl or trait gets a `Contains` edge to the function, and for
+/// impls the function is also `AssociatedWith` the self type, so every method
+/// of a type can be found from the type itself.
+///
+/// `body` is missing for trait methods without a default; otherwise its calls
+/// and metrics are recorded too.
+#[allow(clippy::too_many_arguments)]
+fn process_method(
+    owner: &MethodOwner,
+    method_id: &str,
+    sig: &syn::Signature,
+    body: Option<&syn::Block>,
+    span: SourceSpan,
+    details: ElementDetails,
+    elements: &mut Vec<CodeElement>,
+    relationships: &mut Vec<Relationship>,
+) {
+    let receiver = ReceiverKind::of(sig);
+    let mut details = match owner.item_visibility {
+        Some(visibility) => details.with_visibility(visibility),
+        None => details,
+    }.with_signature(sig);
+    if let Some(body) = body {
+        details = details.with_body(sig, body);
+    }
+    
+    elements.push(CodeElement {
+        id: method_id.to_string(),
+        name: sig.ident.to_string(),
+        element_type: if receiver == ReceiverKind::Static {
+            ElementType::AssociatedFunction
+        } else {
+            ElementType::Method
+        },
+        file_path: owner.file_path.to_string(),
+        module_path: owner.module_path.to_string(),
+        span,
+        receiver: Some(receiver),
+        shape: None,
+        metrics: body.map(|body| FunctionMetrics::of(sig, body)),
+        details,
+    });
+    
+    relationships.push(Relationship {
+        source_id: owner.parent_id.to_string(),
+        target_id: method_id.to_string(),
+        relationship_type: RelationshipType::Contains,
+        derived: false,
+    });
+    
+    if let Some(self_type_id) = owner.self_type_id {
+        relationships.push(Relationship {
+            source_id: method_id.to_string(),
+            target_id: self_type_id.to_string(),
+            relationship_type: RelationshipType::AssociatedWith,
+            derived: false,
+        });
+    }
+    
+    let mut generic_params = owner.generic_params.to_vec();
+    generic_params.extend(type_refs::generic_param_names(&sig.generics));
+    process_signature_types(method_id, sig, &generic_params, owner.self_type, relationships);
+    process_bounds(method_id, &sig.generics, relationships);
+    if let Some(body) = body {
+        process_calls(method_id, calls::collect_calls(body, owner.self_type), relationships);
+        let spawns = tasks::collect_spawns(body, owner.self_type);
+        process_spawns(owner.file_path, owner.module_path, method_id, spawns, owner.self_type, elements, relationships);
+        process_body_items(owner.file_path, method_id, body, owner.cfg, elements, relationships);
+    }
+}
+
+/// Records an associated constant of an impl block or trait, linked to its
+/// owner the same way methods are.
+fn process_associated_const(
+    owner: &MethodOwner,
+    const_id: &str,
+    ty: &syn::Type,
+    span: SourceSpan,
+    details: ElementDetails,
+    elements: &mut Vec<CodeElement>,
+    relationships: &mut Vec<Relationship>,
+) {
+    elements.push(CodeElement {
+        id: const_id.to_string(),
+        name: const_id.rsplit("::").next().unwrap_or(const_id).to_string(),
+        element_type: ElementType::Const,
+        file_path: owner.file_path.to_string(),
+        module_path: owner.module_path.to_string(),
+        span,
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: match owner.item_visibility {
+            Some(visibility) => details.with_visibility(visibility),
+            None => details,
+        },
+    });
+    
+    relationships.push(Relationship {
+        source_id: owner.parent_id.to_string(),
+        target_id: const_id.to_string(),
+        relationship_type: RelationshipType::Contains,
+        derived: false,
+    });
+    
+    if let Some(self_type_id) = owner.self_type_id {
+        relationships.push(Relationship {
+            source_id: const_id.to_string(),
+            target_id: self_type_id.to_string(),
+            relationship_type: RelationshipType::AssociatedWith,
+            derived: false,
+        });
+    }
+    
+    process_type_usage(const_id, std::iter::once(ty), owner.generic_params, owner.self_type, RelationshipType::Uses, relationships);
+}
+
+fn process_const(file_path: &str, module_path: &str, const_item: &ItemConst, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let const_name = const_item.ident.to_string();
+    let const_id = format!("{}::{}", module_path, const_name);
+    
+    elements.push(CodeElement {
+        id: const_id.clone(),
+        name: const_name,
+        element_type: ElementType::Const,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(const_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&const_item.vis, &const_item.attrs, &const_item.generics),
+    });
+    
+    process_type_usage(&const_id, std::iter::once(&*const_item.ty), &[], None, RelationshipType::Uses, relationships);
+}
+
+fn process_static(file_path: &str, module_path: &str, static_item: &ItemStatic, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let static_name = static_item.ident.to_string();
+    let static_id = format!("{}::{}", module_path, static_name);
+    
+    elements.push(CodeElement {
+        id: static_id.clone(),
+        name: static_name,
+        element_type: ElementType::Static,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(static_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&static_item.vis, &static_item.attrs, &syn::Generics::default()),
+    });
+    
+    process_type_usage(&static_id, std::iter::once(&*static_item.ty), &[], None, RelationshipType::Uses, relationships);
+}
+
+fn process_type_alias(file_path: &str, module_path: &str, type_item: &ItemType, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let alias_name = type_item.ident.to_string();
+    let alias_id = format!("{}::{}", module_path, alias_name);
+    
+    let mut details = ElementDetails::of(&type_item.vis, &type_item.attrs, &type_item.generics);
+    // A crate's `type Result<T> = Result<T, Error>` names the error type its
+    // functions return
+    let error_type = errors::ErrorType::of(&type_item.ty);
+    details.error_type = error_type.as_ref().map(|error_type| error_type.display());
+    
+    elements.push(CodeElement {
+        id: alias_id.clone(),
+        name: alias_name,
+        element_type: ElementType::TypeAlias,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(type_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details,
+    });
+    
+    // An alias uses every type in the type it stands for
+    let generic_params = type_refs::generic_param_names(&type_item.generics);
+    process_type_usage(&alias_id, std::iter::once(&*type_item.ty), &generic_params, None, RelationshipType::Uses, relationships);
+    if let Some(error_type) = error_type {
+        process_error_type(&alias_id, error_type, &generic_params, None, relationships);
+    }
+    process_bounds(&alias_id, &type_item.generics, relationships);
+}
+
+fn process_union(file_path: &str, module_path: &str, union_item: &ItemUnion, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let union_name = union_item.ident.to_string();
+    let union_id = format!("{}::{}", module_path, union_name);
+    
+    elements.push(CodeElement {
+        id: union_id.clone(),
+        name: union_name.clone(),
+        element_type: ElementType::Union,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(union_item),
+        receiver: None,
+        shape: Some(TypeShape::Struct(Fields::Named(union_item.fields.named.iter().enumerate().map(|(index, field)| Field::of(index, field)).collect()))),
+        metrics: None,
+        details: ElementDetails::of(&union_item.vis, &union_item.attrs, &union_item.generics),
+    });
+    
+    let generic_params = type_refs::generic_param_names(&union_item.generics);
+    let field_types = union_item.fields.named.iter().map(|field| &field.ty);
+    process_type_usage(&union_id, field_types, &generic_params, Some(&union_name), RelationshipType::DependsOn, relationships);
+    process_bounds(&union_id, &union_item.generics, relationships);
+    process_derives(&union_id, &union_item.attrs, relationships);
+}
+
+/// Records a `macro_rules!` definition. Other macro invocations in item
+/// position (`thread_local! { ... }`) don't define anything we can name.
+fn process_macro(file_path: &str, module_path: &str, macro_item: &ItemMacro, elements: &mut Vec<CodeElement>) {
+    let Some(ident) = &macro_item.ident else {
+        return;
+    };
+    if !macro_item.mac.path.is_ident("macro_rules") {
+        return;
+    }
+    
+    // `#[macro_export]` makes a macro usable from other crates
+    let details = ElementDetails::of(&syn::Visibility::Inherited, &macro_item.attrs, &syn::Generics::default());
+    let details = if macro_item.attrs.iter().any(|attr| attr.path().is_ident("macro_export")) {
+        details.with_visibility(Visibility::Public)
+    } else {
+        details
+    };
+    
+    // Macros live in their own namespace, so `!` keeps them apart from a
+    // function of the same name
+    let macro_name = format!("{}!", ident);
+    elements.push(CodeElement {
+        id: format!("{}::{}", module_path, macro_name),
+        name: macro_name,
+        element_type: ElementType::Macro,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(macro_item),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details,
+    });
+}
+
+/// Records an `extern` block and the functions and statics it declares.
+///
+/// The declared items belong to the enclosing module, like Rust sees them;
+/// the block gets a `Contains` edge to each.
+fn process_foreign_mod(file_path: &str, module_path: &str, foreign_mod: &ItemForeignMod, elements: &mut Vec<CodeElement>, relationships: &mut Vec<Relationship>) {
+    let abi = foreign_mod.abi.name.as_ref()
+        .map(|name| name.value())
+        .unwrap_or_else(|| "C".to_string());
+    
+    let base_block_id = format!("{}::<extern \"{}\">", module_path, abi);
+    let mut block_id = base_block_id.clone();
+    let mut occurrence = 1;
+    while elements.iter().any(|e| e.id == block_id) {
+        occurrence += 1;
+        block_id = format!("{}#{}", base_block_id, occurrence);
+    }
+    
+    elements.push(CodeElement {
+        id: block_id.clone(),
+        name: format!("extern \"{}\"", abi),
+        element_type: ElementType::ExternBlock,
+        file_path: file_path.to_string(),
+        module_path: module_path.to_string(),
+        span: SourceSpan::of(foreign_mod),
+        receiver: None,
+        shape: None,
+        metrics: None,
+        details: ElementDetails::of(&syn::Visibility::Inherited, &foreign_mod.attrs, &syn::Generics::default()),
+    });
+    
+    for item in &foreign_mod.items {
+        // Using anything declared in an extern block is unsafe
+        let (ident, element_type, span, details) = match item {
+            syn::ForeignItem::Fn(func) => (
+                &func.sig.ident,
+                ElementType::ForeignFunction,
+                SourceSpan::of(func),
+                ElementDetails::of(&func.vis, &func.attrs, &func.sig.generics).with_signature(&func.sig).with_unsafe(true),
+            ),
+            syn::ForeignItem::Static(static_item) => (
+                &static_item.ident,
+                ElementType::Static,
+                SourceSpan::of(static_item),
+                ElementDetails::of(&static_item.vis, &static_item.attrs, &syn::Generics::default()).with_unsafe(true),
+            ),
+            _ => continue,
+        };
+        let item_id = format!("{}::{}", module_path, ident);
+        
+        elements.push(CodeElement {
+            id: item_id.clone(),
+            name: ident.to_string(),
+            element_type,
+            file_path: file_path.to_string(),
+            module_path: module_path.to_string(),
+            span,
+            receiver: None,
+            shape: None,
+            metrics: None,
+            details,
+        });
+        
+        relationships.push(Relationship {
+            source_id: block_id.clone(),
+            target_id: item_id.clone(),
+            relationship_type: RelationshipType::Contains,
+            derived: false,
+        });
+        
+        match item {
+            syn::ForeignItem::Fn(func) => {
+                let generic_params = type_refs::generic_param_names(&func.sig.generics);
+                process_signature_types(&item_id, &func.sig, &generic_params, None, relationships);
+            },
+            syn::ForeignItem::Static(static_item) => {
+                process_type_usage(&item_id, std::iter::once(&*static_item.ty), &[], None, RelationshipType::Uses, relationships);
+            },
+            _ => {},
+        }
+    }
+}
+
 fn extract_type_name(ty: &syn::Type) -> String {
     match ty {
         syn::Type::Path(type_path) => {
@@ -274,13 +1669,62 @@ fn extract_type_name(ty: &syn::Type) -> String {
                 .map(|s| s.ident.to_string())
                 .unwrap_or_else(|| "Unknown".to_string())
         },
-        _ => "Unknown".to_string(),
+        // `impl Trait for &T` belongs with `T`
+        syn::Type::Reference(reference) => extract_type_name(&reference.elem),
+        syn::Type::Paren(paren) => extract_type_name(&paren.elem),
+        // Tuples, slices, trait objects and the like have no single name
+        _ => tokens_to_string(ty),
     }
 }
 
+/// Joins the segment names of a path, dropping generic arguments:
+/// `fmt::Display` for `fmt::Display`, `From` for `From<String>`.
+fn plain_path(path: &syn::Path) -> String {
+    path.segments.iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Reads the value of a `#[path = "..."]` attribute.
+fn path_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        syn::Meta::NameValue(name_value) if name_value.path.is_ident("path") => match &name_value.value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. }) => Some(path.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
 #[allow(dead_code)]
 pub fn parse_rust_file(_content: &str) -> Vec<(ElementType, String)> {
     // In a real implementation, this would actually parse Rust code
     // For now, return an empty vec
     vec![]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imports(content: &str) -> Vec<(String, String)> {
+        let parsed = parse_file("src/m.rs", "crate::m", content, &CfgSet::default()).unwrap();
+        parsed.relationships.into_iter()
+            .filter(|rel| rel.relationship_type == RelationshipType::Imports)
+            .map(|rel| (rel.source_id, rel.target_id))
+            .collect()
+    }
+
+    #[test]
+    fn body_imports_are_relative_to_the_enclosing_module() {
+        let content = "fn f() {\n    use super::Foo;\n    use self::bar;\n}\n\
+                       impl S {\n    fn g() {\n        use super::Baz;\n    }\n}\n";
+
+        assert_eq!(imports(content), [
+            ("crate::m::f".to_string(), "crate::Foo".to_string()),
+            ("crate::m::f".to_string(), "crate::m::bar".to_string()),
+            ("crate::m::S::g".to_string(), "crate::Baz".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,143 @@
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, ExprCall, ExprMethodCall, ExprPath, ExprStruct, Token};
+
+use super::tasks;
+
+/// What a function body calls, and the structs it builds.
+#[derive(Debug, Default)]
+pub struct BodyCalls {
+    pub callees: Vec<String>,
+    /// Paths of struct literals (`Point { x, y }`), as written
+    pub constructed: Vec<String>,
+}
+
+/// Collects the callees of every call expression in a function body, in
+/// source order and without duplicates.
+///
+/// Path calls are recorded as written (`helper`, `fs::read`, `Vec::new`), with
+/// a leading `Self` replaced by `self_type` when the body belongs to an impl
+/// or trait. Method calls on `self` become `Type::method`; method calls on any
+/// other receiver are recorded as `.method`, since the receiver type is not
+/// known to a purely syntactic pass. A function passed by a qualified path
+/// without being called (`.map(Variant::of)`) counts as a callee too; a bare
+/// name is more likely a local variable.
+pub fn collect_calls(block: &syn::Block, self_type: Option<&str>) -> BodyCalls {
+    let mut collector = CallCollector {
+        self_type,
+        calls: BodyCalls::default(),
+    };
+    collector.visit_block(block);
+    collector.calls
+}
+
+/// Like `collect_calls`, for the body of a spawned closure or async block.
+pub fn collect_calls_in_expr(expr: &Expr, self_type: Option<&str>) -> BodyCalls {
+    let mut collector = CallCollector {
+        self_type,
+        calls: BodyCalls::default(),
+    };
+    collector.visit_expr(expr);
+    collector.calls
+}
+
+struct CallCollector<'a> {
+    self_type: Option<&'a str>,
+    calls: BodyCalls,
+}
+
+impl CallCollector<'_> {
+    fn push(&mut self, callee: String) {
+        if !self.calls.callees.contains(&callee) {
+            self.calls.callees.push(callee);
+        }
+    }
+    
+    fn visit_args_outside_task(&mut self, args: &Punctuated<Expr, Token![,]>) {
+        let body = tasks::spawned_body(args);
+        for arg in args {
+            if !body.is_some_and(|body| std::ptr::eq(body, arg)) {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for CallCollector<'_> {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(expr_path) = &*call.func {
+            let callee = path_to_string(&expr_path.path, self.self_type);
+            // Enum variant constructors from the prelude are not calls worth drawing
+            if !matches!(callee.as_str(), "Some" | "Ok" | "Err") {
+                self.push(callee);
+            }
+            // A closure spawned as a task makes its calls on its own behalf
+            if expr_path.path.segments.last().is_some_and(|segment| tasks::is_spawn(&segment.ident.to_string())) {
+                self.visit_args_outside_task(&call.args);
+                return;
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let method = call.method.to_string();
+        let callee = match (&*call.receiver, self.self_type) {
+            (Expr::Path(receiver), Some(self_type)) if receiver.path.is_ident("self") => {
+                format!("{}::{}", self_type, method)
+            },
+            _ => format!(".{}", method),
+        };
+        self.push(callee);
+        if tasks::is_spawn(&method) {
+            self.visit_expr(&call.receiver);
+            self.visit_args_outside_task(&call.args);
+            return;
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast ExprPath) {
+        // Capitalized names are enum variants, unit structs and constants
+        let names_function = expr.path.segments.last()
+            .is_some_and(|segment| segment.ident.to_string().starts_with(|c: char| c.is_lowercase() || c == '_'));
+        if expr.qself.is_none() && expr.path.segments.len() > 1 && names_function {
+            self.push(path_to_string(&expr.path, self.self_type));
+        }
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast ExprStruct) {
+        let path = path_to_string(&expr.path, self.self_type);
+        if path != "Self" && !self.calls.constructed.contains(&path) {
+            self.calls.constructed.push(path);
+        }
+        visit::visit_expr_struct(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Macro bodies are opaque tokens; most call-like macros (format!, assert!,
+        // vec!, ...) take comma separated expressions, so try that and move on
+        // quietly if the body has some other shape.
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // Nested items are elements of their own, and their calls are theirs
+    }
+}
+
+pub fn path_to_string(path: &syn::Path, self_type: Option<&str>) -> String {
+    path.segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| match self_type {
+            Some(self_type) if i == 0 && segment.ident == "Self" => self_type.to_string(),
+            _ => segment.ident.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
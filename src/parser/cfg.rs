@@ -0,0 +1,404 @@
+use std::collections::BTreeSet;
+
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Attribute, Item, Meta, Token};
+
+/// Target operating systems offered for cfg evaluation, as `target_os` names.
+pub const TARGET_OSES: [&str; 7] = ["linux", "macos", "windows", "android", "ios", "freebsd", "unknown"];
+
+/// Target architectures offered for cfg evaluation, as `target_arch` names.
+pub const TARGET_ARCHES: [&str; 5] = ["x86_64", "aarch64", "x86", "arm", "wasm32"];
+
+/// The configuration `#[cfg(...)]` attributes are evaluated against.
+///
+/// Defaults to a debug build for the host without tests or features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CfgSet {
+    pub features: BTreeSet<String>,
+    pub target_os: String,
+    pub target_arch: String,
+    pub test: bool,
+    pub debug_assertions: bool,
+}
+
+impl Default for CfgSet {
+    fn default() -> Self {
+        Self {
+            features: BTreeSet::new(),
+            target_os: std::env::consts::OS.to_string(),
+            target_arch: std::env::consts::ARCH.to_string(),
+            test: false,
+            debug_assertions: true,
+        }
+    }
+}
+
+impl CfgSet {
+    /// Whether an item with these attributes is compiled: every `cfg` on it
+    /// must hold. Predicates that can't be parsed are given the benefit of
+    /// the doubt.
+    pub fn is_active(&self, attrs: &[Attribute]) -> bool {
+        attrs.iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .all(|attr| match attr.parse_args::<Meta>() {
+                Ok(predicate) => self.eval(&predicate),
+                Err(_) => true,
+            })
+    }
+
+    /// Whether a module with these attributes is loaded. Test modules are,
+    /// whether or not `test` is on, so that tests can always be found; what's
+    /// inside them is evaluated with `test` on.
+    pub fn loads_module(&self, attrs: &[Attribute]) -> bool {
+        self.is_active(attrs) || (!self.test && self.with_tests().is_active(attrs))
+    }
+
+    /// This set with `test` turned on.
+    pub fn with_tests(&self) -> CfgSet {
+        CfgSet { test: true, ..self.clone() }
+    }
+
+    fn eval(&self, predicate: &Meta) -> bool {
+        match predicate {
+            Meta::Path(path) => {
+                let Some(name) = path.get_ident() else {
+                    return false;
+                };
+                match name.to_string().as_str() {
+                    "test" => self.test,
+                    "debug_assertions" => self.debug_assertions,
+                    "unix" => self.target_family() == "unix",
+                    "windows" => self.target_family() == "windows",
+                    // `doc`, `miri` and custom `--cfg` flags are unset in a normal build
+                    _ => false,
+                }
+            },
+            Meta::List(list) => {
+                let Ok(nested) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+                    return true;
+                };
+                if list.path.is_ident("all") {
+                    nested.iter().all(|p| self.eval(p))
+                } else if list.path.is_ident("any") {
+                    nested.iter().any(|p| self.eval(p))
+                } else if list.path.is_ident("not") {
+                    !nested.iter().all(|p| self.eval(p))
+                } else {
+                    false
+                }
+            },
+            Meta::NameValue(name_value) => {
+                let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) = &name_value.value else {
+                    return false;
+                };
+                let value = value.value();
+                let Some(key) = name_value.path.get_ident() else {
+                    return false;
+                };
+                match key.to_string().as_str() {
+                    "feature" => self.features.contains(&value),
+                    "target_os" => self.target_os == value,
+                    "target_family" => self.target_family() == value,
+                    "target_arch" => self.target_arch == value,
+                    "target_pointer_width" => self.target_pointer_width() == value,
+                    "target_endian" => value == "little",
+                    _ => false,
+                }
+            },
+        }
+    }
+
+    fn target_family(&self) -> &'static str {
+        match self.target_os.as_str() {
+            "windows" => "windows",
+            "unknown" => "wasm",
+            _ => "unix",
+        }
+    }
+
+    fn target_pointer_width(&self) -> &'static str {
+        match self.target_arch.as_str() {
+            "x86_64" | "aarch64" => "64",
+            _ => "32",
+        }
+    }
+}
+
+/// Removes every item, field, variant and statement whose `cfg` attributes
+/// don't hold under `cfg`, so the rest of the parser only sees what would be
+/// compiled.
+///
+/// `mod foo;` declarations are kept: the project still needs to know which
+/// files they lead to, and checks their cfg itself. Test modules are kept
+/// even with `test` off; see [`CfgSet::loads_module`].
+pub fn strip_inactive(file: &mut syn::File, cfg: &CfgSet) {
+    CfgStripper { cfg }.visit_file_mut(file);
+}
+
+/// Names used in `feature = "..."` predicates of the `cfg` and `cfg_attr`
+/// attributes and `cfg!` invocations in `content`, in order of appearance.
+pub fn mentioned_features(content: &str) -> Vec<String> {
+    let Ok((file, _)) = super::recover::parse_file_recovering(content) else {
+        return Vec::new();
+    };
+    let mut collector = FeatureCollector::default();
+    collector.visit_file(&file);
+    collector.features
+}
+
+#[derive(Default)]
+struct FeatureCollector {
+    features: Vec<String>,
+}
+
+impl FeatureCollector {
+    fn collect(&mut self, predicate: &Meta) {
+        match predicate {
+            Meta::Path(_) => {},
+            Meta::List(list) => {
+                if let Ok(nested) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+                    nested.iter().for_each(|p| self.collect(p));
+                }
+            },
+            Meta::NameValue(name_value) => {
+                let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) = &name_value.value else {
+                    return;
+                };
+                let feature = value.value();
+                if name_value.path.is_ident("feature") && !self.features.contains(&feature) {
+                    self.features.push(feature);
+                }
+            },
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FeatureCollector {
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        if attr.path().is_ident("cfg") {
+            if let Ok(predicate) = attr.parse_args::<Meta>() {
+                self.collect(&predicate);
+            }
+        } else if attr.path().is_ident("cfg_attr") {
+            // The predicate, plus any `cfg` among the attributes it applies
+            if let Ok(nested) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+                for meta in nested.iter().take(1) {
+                    self.collect(meta);
+                }
+                for meta in nested.iter().skip(1).filter(|meta| meta.path().is_ident("cfg")) {
+                    self.collect(meta);
+                }
+            }
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if mac.path.is_ident("cfg") {
+            if let Ok(predicate) = mac.parse_body::<Meta>() {
+                self.collect(&predicate);
+            }
+        }
+    }
+}
+
+struct CfgStripper<'a> {
+    cfg: &'a CfgSet,
+}
+
+impl CfgStripper<'_> {
+    fn keeps_item(&self, item: &Item) -> bool {
+        let attrs = match item {
+            Item::Mod(module) if module.content.is_none() => return true,
+            Item::Const(item) => &item.attrs,
+            Item::Enum(item) => &item.attrs,
+            Item::ExternCrate(item) => &item.attrs,
+            Item::Fn(item) => &item.attrs,
+            Item::ForeignMod(item) => &item.attrs,
+            Item::Impl(item) => &item.attrs,
+            Item::Macro(item) => &item.attrs,
+            Item::Mod(item) => return self.cfg.loads_module(&item.attrs),
+            Item::Static(item) => &item.attrs,
+            Item::Struct(item) => &item.attrs,
+            Item::Trait(item) => &item.attrs,
+            Item::TraitAlias(item) => &item.attrs,
+            Item::Type(item) => &item.attrs,
+            Item::Union(item) => &item.attrs,
+            Item::Use(item) => &item.attrs,
+            _ => return true,
+        };
+        self.cfg.is_active(attrs)
+    }
+}
+
+impl VisitMut for CfgStripper<'_> {
+    fn visit_file_mut(&mut self, file: &mut syn::File) {
+        file.items.retain(|item| self.keeps_item(item));
+        visit_mut::visit_file_mut(self, file);
+    }
+
+    fn visit_item_mod_mut(&mut self, module: &mut syn::ItemMod) {
+        if !self.cfg.test && !self.cfg.is_active(&module.attrs) {
+            // A test module kept with tests off; `test` holds inside it
+            let cfg = self.cfg.with_tests();
+            return CfgStripper { cfg: &cfg }.visit_item_mod_mut(module);
+        }
+        if let Some((_, items)) = &mut module.content {
+            items.retain(|item| self.keeps_item(item));
+        }
+        visit_mut::visit_item_mod_mut(self, module);
+    }
+
+    fn visit_item_impl_mut(&mut self, impl_item: &mut syn::ItemImpl) {
+        impl_item.items.retain(|item| match item {
+            syn::ImplItem::Const(item) => self.cfg.is_active(&item.attrs),
+            syn::ImplItem::Fn(item) => self.cfg.is_active(&item.attrs),
+            syn::ImplItem::Type(item) => self.cfg.is_active(&item.attrs),
+            syn::ImplItem::Macro(item) => self.cfg.is_active(&item.attrs),
+            _ => true,
+        });
+        visit_mut::visit_item_impl_mut(self, impl_item);
+    }
+
+    fn visit_item_trait_mut(&mut self, trait_item: &mut syn::ItemTrait) {
+        trait_item.items.retain(|item| match item {
+            syn::TraitItem::Const(item) => self.cfg.is_active(&item.attrs),
+            syn::TraitItem::Fn(item) => self.cfg.is_active(&item.attrs),
+            syn::TraitItem::Type(item) => self.cfg.is_active(&item.attrs),
+            syn::TraitItem::Macro(item) => self.cfg.is_active(&item.attrs),
+            _ => true,
+        });
+        visit_mut::visit_item_trait_mut(self, trait_item);
+    }
+
+    fn visit_item_foreign_mod_mut(&mut self, foreign_mod: &mut syn::ItemForeignMod) {
+        foreign_mod.items.retain(|item| match item {
+            syn::ForeignItem::Fn(item) => self.cfg.is_active(&item.attrs),
+            syn::ForeignItem::Static(item) => self.cfg.is_active(&item.attrs),
+            syn::ForeignItem::Type(item) => self.cfg.is_active(&item.attrs),
+            syn::ForeignItem::Macro(item) => self.cfg.is_active(&item.attrs),
+            _ => true,
+        });
+        visit_mut::visit_item_foreign_mod_mut(self, foreign_mod);
+    }
+
+    fn visit_item_enum_mut(&mut self, enum_item: &mut syn::ItemEnum) {
+        enum_item.variants = std::mem::take(&mut enum_item.variants)
+            .into_iter()
+            .filter(|variant| self.cfg.is_active(&variant.attrs))
+            .collect();
+        visit_mut::visit_item_enum_mut(self, enum_item);
+    }
+
+    fn visit_fields_named_mut(&mut self, fields: &mut syn::FieldsNamed) {
+        fields.named = std::mem::take(&mut fields.named)
+            .into_iter()
+            .filter(|field| self.cfg.is_active(&field.attrs))
+            .collect();
+        visit_mut::visit_fields_named_mut(self, fields);
+    }
+
+    fn visit_fields_unnamed_mut(&mut self, fields: &mut syn::FieldsUnnamed) {
+        fields.unnamed = std::mem::take(&mut fields.unnamed)
+            .into_iter()
+            .filter(|field| self.cfg.is_active(&field.attrs))
+            .collect();
+        visit_mut::visit_fields_unnamed_mut(self, fields);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        block.stmts.retain(|stmt| match stmt {
+            syn::Stmt::Local(local) => self.cfg.is_active(&local.attrs),
+            syn::Stmt::Item(item) => self.keeps_item(item),
+            syn::Stmt::Macro(mac) => self.cfg.is_active(&mac.attrs),
+            syn::Stmt::Expr(..) => true,
+        });
+        visit_mut::visit_block_mut(self, block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linux() -> CfgSet {
+        CfgSet {
+            target_os: "linux".to_string(),
+            target_arch: "x86_64".to_string(),
+            ..CfgSet::default()
+        }
+    }
+
+    fn holds(cfg: &CfgSet, predicate: &str) -> bool {
+        let item: syn::ItemFn = syn::parse_str(&format!("#[cfg({})] fn f() {{}}", predicate)).unwrap();
+        cfg.is_active(&item.attrs)
+    }
+
+    #[test]
+    fn evaluates_feature_and_target_os() {
+        let mut cfg = linux();
+        cfg.features.insert("serde".to_string());
+
+        assert!(holds(&cfg, r#"feature = "serde""#));
+        assert!(!holds(&cfg, r#"feature = "tokio""#));
+        assert!(holds(&cfg, r#"target_os = "linux""#));
+        assert!(!holds(&cfg, r#"target_os = "windows""#));
+        assert!(holds(&cfg, "unix"));
+        assert!(!holds(&cfg, "windows"));
+    }
+
+    #[test]
+    fn evaluates_all_any_and_not() {
+        let cfg = linux();
+
+        assert!(holds(&cfg, r#"all(unix, target_arch = "x86_64")"#));
+        assert!(!holds(&cfg, r#"all(unix, target_os = "macos")"#));
+        assert!(holds(&cfg, r#"any(windows, target_os = "linux")"#));
+        assert!(!holds(&cfg, r#"any(windows, feature = "x")"#));
+        assert!(holds(&cfg, "not(test)"));
+        assert!(!holds(&cfg, "not(debug_assertions)"));
+        assert!(holds(&cfg, r#"all(not(windows), any(test, target_pointer_width = "64"))"#));
+    }
+
+    #[test]
+    fn every_cfg_on_an_item_must_hold() {
+        let mut cfg = linux();
+        let item: syn::ItemFn = syn::parse_str("#[cfg(unix)] #[cfg(test)] fn f() {}").unwrap();
+        assert!(!cfg.is_active(&item.attrs));
+
+        cfg.test = true;
+        assert!(cfg.is_active(&item.attrs));
+    }
+
+    #[test]
+    fn strips_inactive_items() {
+        let mut file: syn::File = syn::parse_str("#[cfg(windows)] fn a() {} #[cfg(unix)] fn b() {} fn c() {}").unwrap();
+        strip_inactive(&mut file, &linux());
+
+        let names: Vec<String> = file.items.iter()
+            .filter_map(|item| match item {
+                Item::Fn(func) => Some(func.sig.ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["b", "c"]);
+    }
+
+    #[test]
+    fn finds_features_in_cfgs_only() {
+        let content = r#"
+            //! Enable with `feature = "docs"`.
+            #[cfg(all(unix, feature = "serde"))]
+            fn a() {}
+            #[cfg_attr(any(feature = "tokio", test), derive(Debug), cfg(not(feature = "sync")))]
+            struct B;
+            fn c() -> bool {
+                let _ = "feature = \"string\"";
+                cfg!(feature = "serde") || cfg!(feature = "trace")
+            }
+        "#;
+
+        assert_eq!(mentioned_features(content), ["serde", "tokio", "sync", "trace"]);
+    }
+}
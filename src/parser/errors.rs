@@ -0,0 +1,155 @@
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Token};
+
+use super::tokens_to_string;
+use super::type_refs;
+
+/// The error half of a `Result` type.
+pub enum ErrorType<'ast> {
+    /// `E` of `Result<T, E>`, as written
+    Written(&'ast syn::Type),
+    /// Path of a one-argument `Result<T>` alias such as `io::Result`, whose
+    /// error type is only known once the project resolves the alias
+    Alias(String),
+}
+
+impl ErrorType<'_> {
+    /// Finds the error type of a function returning `Result`.
+    pub fn of_output(output: &syn::ReturnType) -> Option<ErrorType<'_>> {
+        match output {
+            syn::ReturnType::Type(_, ty) => ErrorType::of(ty),
+            syn::ReturnType::Default => None,
+        }
+    }
+    
+    /// Finds the error type of a `Result` type.
+    pub fn of(ty: &syn::Type) -> Option<ErrorType<'_>> {
+        let syn::Type::Path(path) = ty else {
+            return None;
+        };
+        let last = path.path.segments.last().filter(|segment| segment.ident == "Result")?;
+        let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+            return None;
+        };
+        let type_args: Vec<&syn::Type> = args.args.iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect();
+
+        match type_args[..] {
+            [_, error] => Some(ErrorType::Written(error)),
+            [_] => Some(ErrorType::Alias(super::plain_path(&path.path))),
+            _ => None,
+        }
+    }
+
+    /// The error type as shown to the user, e.g. `Box<dyn Error + Send>`.
+    /// For an alias, the `Error` type that conventionally sits next to it
+    /// (`io::Error` for `io::Result`), until the project resolves it.
+    pub fn display(&self) -> String {
+        match self {
+            ErrorType::Written(ty) => tokens_to_string(*ty),
+            ErrorType::Alias(path) => alias_error_guess(path),
+        }
+    }
+
+    /// Path of the named error type, or of the alias, for a relationship to
+    /// point at. Boxes are looked through; trait objects and generic
+    /// parameters have no path.
+    pub fn path(&self, generic_params: &[String], self_type: Option<&str>) -> Option<String> {
+        match self {
+            ErrorType::Written(ty) => type_path(ty, generic_params, self_type),
+            ErrorType::Alias(path) => Some(path.clone()),
+        }
+    }
+}
+
+/// The conventional error type of a `Result` alias: `io::Error` for
+/// `io::Result`, `Error` for a crate's own `Result`.
+pub fn alias_error_guess(alias: &str) -> String {
+    match alias.rsplit_once("::") {
+        Some((qualifier, _)) => format!("{}::Error", qualifier),
+        None => "Error".to_string(),
+    }
+}
+
+/// The source type `A` of an `impl From<A> for B`, given the trait path.
+pub fn from_source(trait_path: &syn::Path) -> Option<&syn::Type> {
+    let last = trait_path.segments.last().filter(|segment| segment.ident == "From")?;
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(ty)) if args.args.len() == 1 => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Path of the type named by `ty`, with `Self` replaced by the self type.
+/// `Box<T>`, `Rc<T>` and `Arc<T>` give the path of `T`.
+pub fn type_path(ty: &syn::Type, generic_params: &[String], self_type: Option<&str>) -> Option<String> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let last = path.path.segments.last()?;
+    if matches!(last.ident.to_string().as_str(), "Box" | "Rc" | "Arc") {
+        if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                return type_path(inner, generic_params, self_type);
+            }
+        }
+    }
+
+    // The outer path comes first, ahead of the types in its generic
+    // arguments; a generic parameter gives none
+    type_refs::collect_type_refs(ty, generic_params, self_type)
+        .into_iter()
+        .next()
+        .map(|type_ref| type_ref.path)
+}
+
+/// Counts the `?` operators that return from a function body. Those inside
+/// closures and async blocks return from the closure or block instead and
+/// aren't counted.
+pub fn count_try_operators(body: &syn::Block) -> usize {
+    let mut counter = TryCounter { count: 0 };
+    counter.visit_block(body);
+    counter.count
+}
+
+struct TryCounter {
+    count: usize,
+}
+
+impl<'ast> Visit<'ast> for TryCounter {
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        self.count += 1;
+        visit::visit_expr_try(self, expr);
+    }
+
+    fn visit_expr_closure(&mut self, _closure: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _block: &'ast syn::ExprAsync) {}
+
+    fn visit_expr_try_block(&mut self, _block: &'ast syn::ExprTryBlock) {}
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // `?` inside `vec![...]`, `format!(...)` and the like still returns
+        // from the function
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // A `?` in a nested function propagates that function's error, not this one's
+    }
+}
@@ -0,0 +1,124 @@
+use syn::UseTree;
+
+/// Expands a `use` tree into the individual items it imports, flattening
+/// groups (`a::{b, c::d}`) and globs (`a::*`, kept with the trailing `*`).
+/// Each path comes with the name it is bound to in the module: its last
+/// segment, the alias of a rename (`a::B as C`), or `*` for a glob.
+///
+/// `self::` and `super::` prefixes are resolved against `module_path`, so
+/// every crate-local path that uses them starts with `crate`; other paths are
+/// returned as written.
+pub fn expand_use_tree(module_path: &str, tree: &UseTree) -> Vec<(String, String)> {
+    let mut paths = Vec::new();
+    collect(&mut Vec::new(), tree, &mut paths);
+
+    paths.into_iter()
+        .map(|(path, local_name)| (absolute_path(module_path, &path), local_name))
+        .collect()
+}
+
+fn collect(prefix: &mut Vec<String>, tree: &UseTree, paths: &mut Vec<(String, String)>) {
+    match tree {
+        UseTree::Path(path) => {
+            prefix.push(path.ident.to_string());
+            collect(prefix, &path.tree, paths);
+            prefix.pop();
+        },
+        UseTree::Name(syn::UseName { ident }) => {
+            // `use a::{self}` imports the module `a` itself
+            if ident == "self" {
+                let name = prefix.last().cloned().unwrap_or_default();
+                paths.push((prefix.join("::"), name));
+            } else {
+                paths.push((join(prefix, &ident.to_string()), ident.to_string()));
+            }
+        },
+        UseTree::Rename(syn::UseRename { ident, rename, .. }) => {
+            let path = if ident == "self" { prefix.join("::") } else { join(prefix, &ident.to_string()) };
+            paths.push((path, rename.to_string()));
+        },
+        UseTree::Glob(_) => {
+            paths.push((join(prefix, "*"), "*".to_string()));
+        },
+        UseTree::Group(group) => {
+            for item in &group.items {
+                collect(prefix, item, paths);
+            }
+        },
+    }
+}
+
+fn join(prefix: &[String], name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", prefix.join("::"), name)
+    }
+}
+
+/// Rewrites `self::` and `super::` prefixes relative to `module_path`.
+fn absolute_path(module_path: &str, path: &str) -> String {
+    let mut segments = path.split("::").peekable();
+    let mut base: Vec<&str> = module_path.split("::").collect();
+
+    match segments.peek() {
+        Some(&"self") => {
+            segments.next();
+        },
+        Some(&"super") => {
+            while segments.next_if_eq(&"super").is_some() {
+                base.pop();
+            }
+        },
+        _ => return path.to_string(),
+    }
+
+    base.extend(segments);
+    base.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(module_path: &str, tree: &str) -> Vec<(String, String)> {
+        let item: syn::ItemUse = syn::parse_str(&format!("use {};", tree)).unwrap();
+        expand_use_tree(module_path, &item.tree)
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|(path, name)| (path.to_string(), name.to_string())).collect()
+    }
+
+    #[test]
+    fn flattens_groups() {
+        assert_eq!(
+            expand("crate", "std::{fs, io::{self, Read}}"),
+            pairs(&[("std::fs", "fs"), ("std::io", "io"), ("std::io::Read", "Read")]),
+        );
+    }
+
+    #[test]
+    fn binds_renames_to_their_alias() {
+        assert_eq!(
+            expand("crate", "crate::model::{Widget as W, self as m}"),
+            pairs(&[("crate::model::Widget", "W"), ("crate::model", "m")]),
+        );
+        assert_eq!(expand("crate", "std::io::Write as _"), pairs(&[("std::io::Write", "_")]));
+    }
+
+    #[test]
+    fn resolves_self_and_super() {
+        assert_eq!(expand("crate::a::b", "self::c::D"), pairs(&[("crate::a::b::c::D", "D")]));
+        assert_eq!(expand("crate::a::b", "super::E"), pairs(&[("crate::a::E", "E")]));
+        assert_eq!(expand("crate::a::b", "super::super::F"), pairs(&[("crate::F", "F")]));
+    }
+
+    #[test]
+    fn keeps_globs() {
+        assert_eq!(
+            expand("crate::a", "super::{prelude::*, G}"),
+            pairs(&[("crate::prelude::*", "*"), ("crate::G", "G")]),
+        );
+    }
+}
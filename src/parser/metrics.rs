@@ -0,0 +1,371 @@
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Token};
+
+use super::SourceSpan;
+
+/// Size and complexity measurements of one function body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FunctionMetrics {
+    /// Lines of the item that hold code, i.e. neither blank, nor comments,
+    /// nor attributes
+    pub lines: usize,
+    /// McCabe complexity: one plus every branch point (`if`, loop condition,
+    /// extra `match` arm, guard, `&&`/`||`, `?`, `let ... else`)
+    pub cyclomatic: usize,
+    /// Deepest nesting of control flow and closures inside the body
+    pub max_nesting: usize,
+    /// Parameters besides the `self` receiver
+    pub params: usize,
+    pub match_arms: usize,
+    /// `return`s other than a trailing one, and `?` operators
+    pub early_returns: usize,
+}
+
+/// A measurement the graph can size or colour nodes by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Lines,
+    Cyclomatic,
+    Nesting,
+    Params,
+    MatchArms,
+    EarlyReturns,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 6] = [
+        Metric::Lines,
+        Metric::Cyclomatic,
+        Metric::Nesting,
+        Metric::Params,
+        Metric::MatchArms,
+        Metric::EarlyReturns,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Lines => "Lines of code",
+            Metric::Cyclomatic => "Cyclomatic complexity",
+            Metric::Nesting => "Nesting depth",
+            Metric::Params => "Parameters",
+            Metric::MatchArms => "Match arms",
+            Metric::EarlyReturns => "Early returns",
+        }
+    }
+
+    /// A value from which a function is commonly considered too big or
+    /// complex, used to scale sizes and colours.
+    pub fn threshold(self) -> usize {
+        match self {
+            Metric::Lines => 60,
+            Metric::Cyclomatic => 10,
+            Metric::Nesting => 4,
+            Metric::Params => 5,
+            Metric::MatchArms => 12,
+            Metric::EarlyReturns => 5,
+        }
+    }
+}
+
+impl FunctionMetrics {
+    /// Measures everything but `lines`, which needs the source text; see
+    /// `count_code_lines`.
+    pub fn of(sig: &syn::Signature, body: &syn::Block) -> Self {
+        let mut counter = ComplexityCounter::default();
+        counter.visit_block(body);
+
+        // A `return` that ends the body isn't early
+        let trailing_return = matches!(body.stmts.last(), Some(syn::Stmt::Expr(Expr::Return(_), _)));
+
+        Self {
+            lines: 0,
+            cyclomatic: 1 + counter.branches,
+            max_nesting: counter.max_depth,
+            params: sig.inputs.iter().filter(|input| matches!(input, syn::FnArg::Typed(_))).count(),
+            match_arms: counter.match_arms,
+            early_returns: counter.returns - usize::from(trailing_return),
+        }
+    }
+
+    pub fn get(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Lines => self.lines,
+            Metric::Cyclomatic => self.cyclomatic,
+            Metric::Nesting => self.max_nesting,
+            Metric::Params => self.params,
+            Metric::MatchArms => self.match_arms,
+            Metric::EarlyReturns => self.early_returns,
+        }
+    }
+
+    /// One-line summary, e.g. for the status bar.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} LOC, complexity {}, nesting {}, {} params, {} match arms, {} early returns",
+            self.lines, self.cyclomatic, self.max_nesting, self.params, self.match_arms, self.early_returns
+        )
+    }
+}
+
+/// Counts the lines within `span` that hold code, skipping blank lines,
+/// comments (`//` and `/* */`, which may span lines) and lines that only
+/// carry attributes.
+pub fn count_code_lines(content: &str, span: SourceSpan) -> usize {
+    if span.start_line == 0 {
+        return 0;
+    }
+    let mut scanner = LineScanner::default();
+    let mut attribute_depth = 0;
+    let mut count = 0;
+    for line in content.lines().skip(span.start_line - 1).take(span.line_count()) {
+        let code = scanner.strip_comments(line);
+        let mut code = code.trim();
+        if attribute_depth > 0 {
+            // The rest of an attribute spread over several lines
+            match close_attribute(code, &mut attribute_depth) {
+                Some(rest) => code = rest,
+                None => continue,
+            }
+        }
+        while let Some(attribute) = code.strip_prefix("#!").or_else(|| code.strip_prefix('#')) {
+            match close_attribute(attribute, &mut attribute_depth) {
+                Some(rest) => code = rest,
+                None => break,
+            }
+        }
+        if attribute_depth == 0 && !code.is_empty() {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Follows the brackets of an attribute into `line`, returning what comes
+/// after it, or `None` when it continues on the next line.
+fn close_attribute<'a>(line: &'a str, depth: &mut usize) -> Option<&'a str> {
+    for (index, c) in line.char_indices() {
+        match c {
+            '[' => *depth += 1,
+            ']' => {
+                *depth = depth.saturating_sub(1);
+                if *depth == 0 {
+                    return Some(line[index + 1..].trim_start());
+                }
+            },
+            _ if *depth == 0 => return Some(line),
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Removes comments from source lines fed in order, remembering block
+/// comments and string literals that run on to the next line.
+#[derive(Default)]
+struct LineScanner {
+    /// Nesting of the `/* */` comments open at the end of the last line
+    comment_depth: usize,
+    in_string: bool,
+}
+
+impl LineScanner {
+    fn strip_comments(&mut self, line: &str) -> String {
+        let mut code = String::with_capacity(line.len());
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.comment_depth > 0 {
+                match (c, chars.peek()) {
+                    ('*', Some('/')) => {
+                        chars.next();
+                        self.comment_depth -= 1;
+                    },
+                    ('/', Some('*')) => {
+                        chars.next();
+                        self.comment_depth += 1;
+                    },
+                    _ => {},
+                }
+                continue;
+            }
+            if self.in_string {
+                match c {
+                    '\\' => {
+                        code.push(c);
+                        chars.next();
+                    },
+                    '"' => self.in_string = false,
+                    _ => {},
+                }
+                code.push(c);
+                continue;
+            }
+            // `'"'` is a char, not the start of a string
+            if c == '\'' && chars.clone().take(3).collect::<String>().trim_start_matches('\\').starts_with("\"'") {
+                code.push(c);
+                while let Some(c) = chars.next_if(|&c| c != '\'') {
+                    code.push(c);
+                }
+                continue;
+            }
+            match (c, chars.peek()) {
+                ('/', Some('/')) => break,
+                ('/', Some('*')) => {
+                    chars.next();
+                    self.comment_depth += 1;
+                },
+                ('"', _) => {
+                    self.in_string = true;
+                    code.push(c);
+                },
+
+                _ => code.push(c),
+            }
+        }
+        code
+    }
+}
+
+#[derive(Default)]
+struct ComplexityCounter {
+    branches: usize,
+    depth: usize,
+    max_depth: usize,
+    match_arms: usize,
+    returns: usize,
+    /// Closures and async blocks being visited; `return` and `?` inside them
+    /// leave the closure, not the function
+    closure_depth: usize,
+}
+
+impl ComplexityCounter {
+    fn nested(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+        visit(self);
+        self.depth -= 1;
+    }
+
+    fn closure(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.closure_depth += 1;
+        self.nested(visit);
+        self.closure_depth -= 1;
+    }
+}
+
+impl<'ast> Visit<'ast> for ComplexityCounter {
+    fn visit_expr_if(&mut self, expr: &'ast syn::ExprIf) {
+        self.branches += 1;
+        self.nested(|this| {
+            this.visit_expr(&expr.cond);
+            this.visit_block(&expr.then_branch);
+        });
+        match expr.else_branch.as_ref().map(|(_, else_branch)| &**else_branch) {
+            // `else if` continues the chain at the same depth
+            Some(Expr::If(else_if)) => self.visit_expr_if(else_if),
+            Some(else_branch) => self.nested(|this| this.visit_expr(else_branch)),
+            None => {},
+        }
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast syn::ExprMatch) {
+        self.branches += expr.arms.len().saturating_sub(1);
+        self.branches += expr.arms.iter().filter(|arm| arm.guard.is_some()).count();
+        self.match_arms += expr.arms.len();
+        self.nested(|this| visit::visit_expr_match(this, expr));
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.branches += 1;
+        self.nested(|this| visit::visit_expr_while(this, expr));
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.branches += 1;
+        self.nested(|this| visit::visit_expr_for_loop(this, expr));
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        // A bare `loop` only branches where it breaks, which is counted there
+        self.nested(|this| visit::visit_expr_loop(this, expr));
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast syn::ExprClosure) {
+        self.closure(|this| visit::visit_expr_closure(this, expr));
+    }
+
+    fn visit_expr_async(&mut self, expr: &'ast syn::ExprAsync) {
+        self.closure(|this| visit::visit_expr_async(this, expr));
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if matches!(expr.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) {
+            self.branches += 1;
+        }
+        visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        self.branches += 1;
+        if self.closure_depth == 0 {
+            self.returns += 1;
+        }
+        visit::visit_expr_try(self, expr);
+    }
+
+    fn visit_expr_return(&mut self, expr: &'ast syn::ExprReturn) {
+        if self.closure_depth == 0 {
+            self.returns += 1;
+        }
+        visit::visit_expr_return(self, expr);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if local.init.as_ref().is_some_and(|init| init.diverge.is_some()) {
+            self.branches += 1;
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // As for calls, look into macros whose body is a list of expressions
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // A nested function's lines and branches go into its own metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> usize {
+        let span = SourceSpan { start_line: 1, start_column: 1, end_line: content.lines().count(), end_column: 1 };
+        count_code_lines(content, span)
+    }
+
+    #[test]
+    fn counts_code_but_not_comments_or_blank_lines() {
+        assert_eq!(lines("fn f() {\n\n    // note\n    g(); // call\n}\n"), 3);
+    }
+
+    #[test]
+    fn skips_block_comments() {
+        let content = "fn f() {\n    /* one\n       two */\n    let a = 1; /* trailing\n    */\n    /* a */ g(a);\n}\n";
+        assert_eq!(lines(content), 4);
+        assert_eq!(lines("fn f() {\n    let s = \"/* not a comment\";\n    g(s);\n}\n"), 4);
+        assert_eq!(lines("fn f(c: char) -> bool {\n    c == '\"' /* a quote\n    char */\n        || c == '\\\"'\n}\n"), 4);
+    }
+
+    #[test]
+    fn skips_attribute_lines() {
+        let content = "#[inline]\n#[cfg(any(\n    unix,\n    windows,\n))]\n#[must_use] fn f() {\n    #![allow(unused)]\n    1\n}\n";
+        assert_eq!(lines(content), 3);
+    }
+}
@@ -0,0 +1,110 @@
+use proc_macro2::{Delimiter, TokenTree};
+use syn::parse::discouraged::Speculative;
+use syn::parse::{ParseStream, Parser};
+use syn::{Attribute, Item};
+
+use super::SyntaxError;
+
+/// Parses a whole file, falling back to parsing it item by item when that
+/// fails, so one item in syntax `syn` doesn't understand doesn't cost the
+/// rest of the file.
+///
+/// Returns the file made of every item that parsed together with an error
+/// for each one that didn't. Only a file that can't even be split into
+/// tokens (unbalanced delimiters, unterminated strings) fails as a whole.
+pub fn parse_file_recovering(content: &str) -> Result<(syn::File, Vec<SyntaxError>), SyntaxError> {
+    let first_error = match syn::parse_file(content) {
+        Ok(file) => return Ok((file, Vec::new())),
+        Err(error) => error,
+    };
+
+    let parser = |input: ParseStream| {
+        let attrs = input.call(Attribute::parse_inner).unwrap_or_default();
+        let mut items = Vec::new();
+        let mut errors = Vec::new();
+
+        while !input.is_empty() {
+            let fork = input.fork();
+            match fork.parse::<Item>() {
+                Ok(item) => {
+                    input.advance_to(&fork);
+                    items.push(item);
+                },
+                Err(error) => {
+                    errors.push(SyntaxError::from(&error));
+                    skip_item(input)?;
+                },
+            }
+        }
+
+        Ok((attrs, items, errors))
+    };
+
+    let (attrs, items, errors) = parser.parse_str(strip_preamble(content))
+        .map_err(|_| SyntaxError::from(&first_error))?;
+
+    Ok((syn::File { shebang: None, attrs, items }, errors))
+}
+
+/// Skips the tokens of an item that failed to parse: everything up to and
+/// including the next `;` or `{ ... }` block at the top level.
+fn skip_item(input: ParseStream) -> syn::Result<()> {
+    while !input.is_empty() {
+        match input.parse::<TokenTree>()? {
+            TokenTree::Punct(punct) if punct.as_char() == ';' => break,
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => break,
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Drops a byte order mark and a leading `#!` interpreter line, which
+/// `syn::parse_file` handles but the item parser doesn't. `#![attr]` is an
+/// inner attribute, not a shebang.
+fn strip_preamble(content: &str) -> &str {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    match content.strip_prefix("#!") {
+        Some(rest) if !rest.trim_start().starts_with('[') => rest.find('\n').map_or("", |end| &rest[end..]),
+        _ => content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_names(file: &syn::File) -> Vec<String> {
+        file.items.iter()
+            .filter_map(|item| match item {
+                Item::Fn(func) => Some(func.sig.ident.to_string()),
+                Item::Struct(item) => Some(item.ident.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_items_around_a_syntax_error() {
+        let content = "fn before() {}\nfn broken() -> {}\nstruct After;\nfn last() {}\n";
+        let (file, errors) = parse_file_recovering(content).unwrap();
+
+        assert_eq!(item_names(&file), ["before", "After", "last"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 2);
+    }
+
+    #[test]
+    fn a_valid_file_has_no_errors() {
+        let (file, errors) = parse_file_recovering("#![allow(unused)]\nfn a() {}\n").unwrap();
+
+        assert_eq!(item_names(&file), ["a"]);
+        assert_eq!(file.attrs.len(), 1);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn unbalanced_delimiters_fail_the_whole_file() {
+        assert!(parse_file_recovering("fn a() {\nfn b() {}\n").is_err());
+    }
+}
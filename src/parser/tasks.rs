@@ -0,0 +1,169 @@
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Token};
+
+use super::calls::path_to_string;
+
+/// A task or thread started in a function body.
+pub struct Spawn<'ast> {
+    /// The spawning function or method as written, e.g. `tokio::spawn` or
+    /// `.spawn` for `builder.spawn(...)`
+    pub spawner: String,
+    pub task: SpawnedTask<'ast>,
+}
+
+pub enum SpawnedTask<'ast> {
+    /// A function spawned by name (`thread::spawn(worker)`) or whose future is
+    /// spawned (`tokio::spawn(worker(rx))`), written like a callee
+    Function(String),
+    /// A closure or async block spawned in place
+    Body(&'ast Expr),
+}
+
+/// Whether a function or method with this name runs its argument as a
+/// separate task or thread, like `tokio::spawn`, `std::thread::spawn`,
+/// `async_std::task::spawn`, `spawn_blocking` or a scoped `s.spawn(...)`.
+pub fn is_spawn(name: &str) -> bool {
+    matches!(name, "spawn" | "spawn_blocking" | "spawn_local")
+}
+
+/// The argument of a spawn call that is the task's own code, when it's a
+/// closure or async block written in place.
+pub fn spawned_body<'a>(args: impl IntoIterator<Item = &'a Expr>) -> Option<&'a Expr> {
+    args.into_iter()
+        .next()
+        .filter(|arg| matches!(arg, Expr::Closure(_) | Expr::Async(_)))
+}
+
+/// Collects the spawn calls of a function body, in source order. Spawns
+/// nested inside a spawned closure belong to that task and aren't included.
+pub fn collect_spawns<'ast>(block: &'ast syn::Block, self_type: Option<&str>) -> Vec<Spawn<'ast>> {
+    let mut collector = TaskCollector::new(self_type);
+    collector.visit_block(block);
+    collector.spawns
+}
+
+/// Like `collect_spawns`, for the body of a spawned closure or async block.
+pub fn collect_spawns_in_expr<'ast>(expr: &'ast Expr, self_type: Option<&str>) -> Vec<Spawn<'ast>> {
+    let mut collector = TaskCollector::new(self_type);
+    collector.visit_expr(expr);
+    collector.spawns
+}
+
+/// Counts the `.await` points of a function body, leaving out those of
+/// spawned tasks, which don't suspend the function itself.
+pub fn count_awaits(block: &syn::Block) -> usize {
+    let mut collector = TaskCollector::new(None);
+    collector.visit_block(block);
+    collector.awaits
+}
+
+/// Like `count_awaits`, for the body of a spawned closure or async block.
+pub fn count_awaits_in_expr(expr: &Expr) -> usize {
+    let mut collector = TaskCollector::new(None);
+    collector.visit_expr(expr);
+    collector.awaits
+}
+
+struct TaskCollector<'a, 'ast> {
+    self_type: Option<&'a str>,
+    spawns: Vec<Spawn<'ast>>,
+    awaits: usize,
+}
+
+impl<'a, 'ast> TaskCollector<'a, 'ast> {
+    fn new(self_type: Option<&'a str>) -> Self {
+        Self {
+            self_type,
+            spawns: Vec::new(),
+            awaits: 0,
+        }
+    }
+
+    /// Records a spawn call and visits its arguments, except for a spawned
+    /// body, which is analyzed as a task of its own.
+    fn spawn(&mut self, spawner: String, args: &'ast Punctuated<Expr, Token![,]>) {
+        let body = spawned_body(args);
+        let task = match (body, args.first()) {
+            (Some(body), _) => Some(SpawnedTask::Body(body)),
+            (None, Some(arg)) => self.spawned_function(arg).map(SpawnedTask::Function),
+            (None, None) => None,
+        };
+        if let Some(task) = task {
+            self.spawns.push(Spawn { spawner, task });
+        }
+
+        for arg in args {
+            if !body.is_some_and(|body| std::ptr::eq(body, arg)) {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn spawned_function(&self, arg: &Expr) -> Option<String> {
+        match arg {
+            Expr::Path(path) => Some(path_to_string(&path.path, self.self_type)),
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => Some(path_to_string(&path.path, self.self_type)),
+                _ => None,
+            },
+            Expr::MethodCall(call) => Some(match (&*call.receiver, self.self_type) {
+                (Expr::Path(receiver), Some(self_type)) if receiver.path.is_ident("self") => {
+                    format!("{}::{}", self_type, call.method)
+                },
+                _ => format!(".{}", call.method),
+            }),
+            Expr::Paren(paren) => self.spawned_function(&paren.expr),
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TaskCollector<'_, 'ast> {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            if func.path.segments.last().is_some_and(|segment| is_spawn(&segment.ident.to_string())) {
+                self.visit_expr(&call.func);
+                self.spawn(path_to_string(&func.path, self.self_type), &call.args);
+                return;
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if is_spawn(&call.method.to_string()) {
+            self.visit_expr(&call.receiver);
+            self.spawn(format!(".{}", call.method), &call.args);
+            return;
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_await(&mut self, expr: &'ast syn::ExprAwait) {
+        self.awaits += 1;
+        visit::visit_expr_await(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Macro bodies are opaque tokens, parsed here into expressions that
+        // don't outlive this call, so closures spawned inside a macro
+        // (`select!`, `join!`) can't be kept; spawned functions can
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            let mut inner = TaskCollector::new(self.self_type);
+            for arg in &args {
+                inner.visit_expr(arg);
+            }
+            self.awaits += inner.awaits;
+            for spawn in inner.spawns {
+                if let SpawnedTask::Function(function) = spawn.task {
+                    self.spawns.push(Spawn { spawner: spawn.spawner, task: SpawnedTask::Function(function) });
+                }
+            }
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // Tasks spawned in a nested function are that function's own
+    }
+}
@@ -0,0 +1,222 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
+
+/// Renders a syntax node back to compact source text, e.g. `Vec<u8>` rather
+/// than the spaced-out `Vec < u8 >` a token stream prints.
+///
+/// Spaces are decided token by token: joint punctuation such as `::`, `->`
+/// or `<<` is never split, generic brackets hug what they enclose, and the
+/// same `<` or `>` stays spaced where it is an operator.
+pub fn tokens_to_string<T: ToTokens>(node: &T) -> String {
+    let mut writer = TokenWriter::default();
+    writer.write_stream(node.to_token_stream(), false);
+    writer.text
+}
+
+/// What was written last, which decides whether a space goes before the next
+/// token.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Written {
+    /// Nothing yet, or an opening delimiter
+    #[default]
+    Nothing,
+    /// An identifier, literal or lifetime
+    Word,
+    /// A keyword after which `&`, `*` and `-` are prefixes: `as *const u8`
+    Keyword,
+    /// A closing delimiter
+    Close,
+    /// A generic `>`
+    AngleClose,
+    /// Punctuation that binds to what follows: `&`, `*`, `#`, `::`, `.`, a
+    /// generic `<`
+    Prefix,
+    /// Punctuation joined to the next character, like the `-` of `->`
+    Joint,
+    /// The `!` of a macro call
+    Bang,
+    /// Punctuation followed by a space: `,`, `:`, `=`, binary operators
+    Spaced,
+}
+
+#[derive(Default)]
+struct TokenWriter {
+    text: String,
+    last: Written,
+    /// The punctuation written since the last other token, e.g. `::`
+    op: String,
+    /// Generic brackets open in the current group
+    angle_depth: usize,
+}
+
+const PREFIX_KEYWORDS: [&str; 10] = ["as", "mut", "dyn", "impl", "return", "in", "where", "let", "else", "move"];
+
+impl TokenWriter {
+    fn write_stream(&mut self, stream: TokenStream, expr: bool) {
+        let tokens: Vec<TokenTree> = stream.into_iter().collect();
+        for (index, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Ident(ident) => {
+                    let word = ident.to_string();
+                    self.space();
+                    self.text.push_str(&word);
+                    self.last = if PREFIX_KEYWORDS.contains(&word.as_str()) { Written::Keyword } else { Written::Word };
+                    self.op.clear();
+                },
+                TokenTree::Literal(literal) => {
+                    self.space();
+                    self.text.push_str(&literal.to_string());
+                    self.last = Written::Word;
+                    self.op.clear();
+                },
+                TokenTree::Punct(punct) => {
+                    let next = match tokens.get(index + 1) {
+                        Some(TokenTree::Punct(next)) if punct.spacing() == Spacing::Joint => Some(next.as_char()),
+                        _ => None,
+                    };
+                    self.write_punct(punct.as_char(), next, expr);
+                },
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::None => {
+                            self.write_stream(group.stream(), expr);
+                            continue;
+                        },
+                    };
+                    // Calls and parenthesized arguments hug the name before
+                    // them: `foo(x)`, `derive(Debug)`, `pub(crate)`, `run<T>(x)`
+                    let hugs = group.delimiter() == Delimiter::Parenthesis
+                        && matches!(self.last, Written::Word | Written::AngleClose);
+                    if !hugs {
+                        self.space();
+                    }
+                    self.text.push_str(open);
+
+                    let brace = group.delimiter() == Delimiter::Brace;
+                    let spaced = brace && !group.stream().is_empty();
+                    if spaced {
+                        self.text.push(' ');
+                    }
+                    let angle_depth = std::mem::take(&mut self.angle_depth);
+                    self.last = Written::Nothing;
+                    self.op.clear();
+                    self.write_stream(group.stream(), expr || brace);
+                    self.angle_depth = angle_depth;
+                    if spaced {
+                        self.text.push(' ');
+                    }
+
+                    self.text.push_str(close);
+                    self.last = Written::Close;
+                    self.op.clear();
+                },
+            }
+        }
+    }
+
+    /// Writes one punctuation character; `next` is the one it is joined to.
+    fn write_punct(&mut self, c: char, next: Option<char>, expr: bool) {
+        if self.last == Written::Joint {
+            // The rest of `::`, `->`, `<<` and the like
+            self.op.push(c);
+            self.text.push(c);
+            self.last = match (next, self.op.as_str()) {
+                (Some(_), _) => Written::Joint,
+                (None, "::" | ".." | "..=") => Written::Prefix,
+                (None, _) => Written::Spaced,
+            };
+            return;
+        }
+
+        let after_path = self.op == "::";
+        self.op.clear();
+        self.op.push(c);
+        let follows_operand = matches!(self.last, Written::Word | Written::Close | Written::AngleClose);
+        let (space_before, written) = match c {
+            '>' if self.angle_depth > 0 => {
+                self.angle_depth -= 1;
+                (false, Written::AngleClose)
+            },
+            '<' if after_path || (!expr && !matches!(next, Some('<' | '='))) => {
+                self.angle_depth += 1;
+                (matches!(self.last, Written::Spaced | Written::Close), Written::Prefix)
+            },
+            ',' | ';' => (false, Written::Spaced),
+            ':' if next == Some(':') => (false, Written::Joint),
+            ':' => (false, Written::Spaced),
+            '.' if next.is_some() => (false, Written::Joint),
+            '.' => (false, Written::Prefix),
+            '\'' => (true, Written::Joint),
+            '#' => (true, Written::Prefix),
+            '!' if next.is_some() => (true, Written::Joint),
+            '!' if self.last == Written::Word => (false, Written::Bang),
+            '?' if follows_operand => (false, Written::Word),
+            '!' | '?' => (true, Written::Prefix),
+            '&' | '*' | '-' if next.is_some() => (true, Written::Joint),
+            '&' | '*' | '-' if follows_operand => (true, Written::Spaced),
+            '&' | '*' | '-' => (true, Written::Prefix),
+            _ if next.is_some() => (true, Written::Joint),
+            _ => (true, Written::Spaced),
+        };
+        if space_before {
+            self.space();
+        }
+        self.text.push(c);
+        self.last = written;
+    }
+
+    fn space(&mut self) {
+        if !matches!(self.last, Written::Nothing | Written::Prefix | Written::Joint | Written::Bang) {
+            self.text.push(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_type(source: &str) -> String {
+        tokens_to_string(&syn::parse_str::<syn::Type>(source).unwrap())
+    }
+
+    #[test]
+    fn compacts_types() {
+        assert_eq!(render_type("Vec < u8 >"), "Vec<u8>");
+        assert_eq!(render_type("Result<Vec<Vec<u8>>, io::Error>"), "Result<Vec<Vec<u8>>, io::Error>");
+        assert_eq!(render_type("& 'a mut [u8]"), "&'a mut [u8]");
+        assert_eq!(render_type("*const T"), "*const T");
+        assert_eq!(render_type("<Self as fmt::Display>::Output"), "<Self as fmt::Display>::Output");
+        assert_eq!(render_type("Box<dyn Fn(&str) -> (A, B) + Send>"), "Box<dyn Fn(&str) -> (A, B) + Send>");
+        assert_eq!(render_type("[u8; 4]"), "[u8; 4]");
+    }
+
+    #[test]
+    fn compacts_signatures_and_attributes() {
+        let func: syn::ItemFn = syn::parse_str(
+            "#[derive(Debug)] pub(crate) fn f<X: A + ?Sized>(x: &X, y: impl Iterator<Item = u8>) -> Option<X> where X: Clone {}",
+        ).unwrap();
+
+        assert_eq!(tokens_to_string(&func.attrs[0].meta), "derive(Debug)");
+        assert_eq!(tokens_to_string(&func.vis), "pub(crate)");
+        assert_eq!(
+            tokens_to_string(&func.sig),
+            "fn f<X: A + ?Sized>(x: &X, y: impl Iterator<Item = u8>) -> Option<X> where X: Clone"
+        );
+    }
+
+    #[test]
+    fn keeps_operators_apart() {
+        assert_eq!(render_type("Bits<{ N << 1 }>"), "Bits<{ N << 1 }>");
+        assert_eq!(render_type("Bits<{ N >> 1 }>"), "Bits<{ N >> 1 }>");
+        assert_eq!(render_type("Check<{ N <= M }>"), "Check<{ N <= M }>");
+        assert_eq!(render_type("Check<{ N > 1 }>"), "Check<{ N > 1 }>");
+        assert_eq!(render_type("Len<{ size_of::<u8>() * 2 }>"), "Len<{ size_of::<u8>() * 2 }>");
+
+        let discriminant: syn::Expr = syn::parse_str("1 << 3 | -2").unwrap();
+        assert_eq!(tokens_to_string(&discriminant), "1 << 3 | -2");
+    }
+}
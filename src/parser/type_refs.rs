@@ -0,0 +1,95 @@
+use syn::visit::{self, Visit};
+
+/// A named type mentioned somewhere inside a type expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeRef {
+    /// Path as written (`Foo`, `crate::model::Foo`, `io::Error`), with a
+    /// leading `Self` replaced by the enclosing self type
+    pub path: String,
+    /// Whether the type is only reached through `&`, `&mut` or a raw pointer
+    pub behind_reference: bool,
+}
+
+/// Collects the named types used in `ty`, including ones nested in generic
+/// arguments (`Vec<Foo>`, `HashMap<K, Bar>`) and trait objects.
+///
+/// Primitives and the names in `generic_params` are skipped, since neither
+/// can be an element of the project.
+pub fn collect_type_refs(ty: &syn::Type, generic_params: &[String], self_type: Option<&str>) -> Vec<TypeRef> {
+    let mut collector = TypeRefCollector {
+        generic_params,
+        self_type,
+        reference_depth: 0,
+        refs: Vec::new(),
+    };
+    collector.visit_type(ty);
+    collector.refs
+}
+
+/// Names of the type parameters declared by `generics`.
+pub fn generic_param_names(generics: &syn::Generics) -> Vec<String> {
+    generics.type_params().map(|param| param.ident.to_string()).collect()
+}
+
+struct TypeRefCollector<'a> {
+    generic_params: &'a [String],
+    self_type: Option<&'a str>,
+    reference_depth: usize,
+    refs: Vec<TypeRef>,
+}
+
+impl<'ast> Visit<'ast> for TypeRefCollector<'_> {
+    fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+        self.reference_depth += 1;
+        visit::visit_type_reference(self, reference);
+        self.reference_depth -= 1;
+    }
+
+    fn visit_type_ptr(&mut self, ptr: &'ast syn::TypePtr) {
+        self.reference_depth += 1;
+        visit::visit_type_ptr(self, ptr);
+        self.reference_depth -= 1;
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let mut segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+
+        let is_builtin = segments.len() == 1
+            && (self.generic_params.contains(&segments[0]) || is_primitive(&segments[0]));
+        if !is_builtin {
+            if segments[0] == "Self" {
+                match self.self_type {
+                    Some(self_type) => segments[0] = self_type.to_string(),
+                    None => segments.clear(),
+                }
+            }
+
+            if !segments.is_empty() {
+                let type_ref = TypeRef {
+                    path: segments.join("::"),
+                    behind_reference: self.reference_depth > 0,
+                };
+                if !self.refs.contains(&type_ref) {
+                    self.refs.push(type_ref);
+                }
+            }
+        }
+
+        // Generic arguments hold further types
+        visit::visit_path(self, path);
+    }
+
+    fn visit_expr(&mut self, _expr: &'ast syn::Expr) {
+        // Array lengths and const generic arguments aren't type usage
+    }
+}
+
+fn is_primitive(name: &str) -> bool {
+    matches!(
+        name,
+        "bool" | "char" | "str"
+            | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            | "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+            | "f32" | "f64"
+    )
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Pat, Token, Type};
+
+/// Counts the `unsafe` blocks in a function body and the raw-pointer
+/// dereferences inside unsafe code, returned in that order.
+///
+/// Whether a dereference is of a raw pointer can only be guessed without type
+/// information: it counts when the operand is a binding declared or
+/// initialized as a raw pointer, a cast to a raw pointer, or a call to a
+/// pointer method such as `as_ptr` or `add`.
+pub fn count_unsafe(sig: &syn::Signature, body: &syn::Block) -> (usize, usize) {
+    let mut counter = UnsafeCounter {
+        blocks: 0,
+        raw_derefs: 0,
+        unsafe_depth: usize::from(sig.unsafety.is_some()),
+        raw_pointers: HashSet::new(),
+    };
+    for input in &sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = input {
+            if let (Pat::Ident(pat), Type::Ptr(_)) = (&*pat_type.pat, &*pat_type.ty) {
+                counter.raw_pointers.insert(pat.ident.to_string());
+            }
+        }
+    }
+    counter.visit_block(body);
+    (counter.blocks, counter.raw_derefs)
+}
+
+struct UnsafeCounter {
+    blocks: usize,
+    raw_derefs: usize,
+    /// Enclosing `unsafe` blocks, plus one for an `unsafe fn`
+    unsafe_depth: usize,
+    /// Bindings known to hold a raw pointer
+    raw_pointers: HashSet<String>,
+}
+
+impl UnsafeCounter {
+    fn is_raw_pointer(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => path.path.get_ident().is_some_and(|ident| self.raw_pointers.contains(&ident.to_string())),
+            Expr::Cast(cast) => matches!(&*cast.ty, Type::Ptr(_)),
+            Expr::MethodCall(call) => matches!(
+                call.method.to_string().as_str(),
+                "as_ptr" | "as_mut_ptr" | "cast" | "cast_mut" | "cast_const" | "add" | "sub" | "offset"
+                    | "byte_add" | "byte_sub" | "byte_offset" | "wrapping_add" | "wrapping_sub" | "wrapping_offset"
+            ),
+            Expr::Call(call) => matches!(
+                &*call.func,
+                Expr::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "null" || s.ident == "null_mut")
+            ),
+            Expr::Macro(mac) => mac.mac.path.segments.last().is_some_and(|s| s.ident == "addr_of" || s.ident == "addr_of_mut"),
+            Expr::Paren(paren) => self.is_raw_pointer(&paren.expr),
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnsafeCounter {
+    fn visit_expr_unsafe(&mut self, expr: &'ast syn::ExprUnsafe) {
+        self.blocks += 1;
+        self.unsafe_depth += 1;
+        visit::visit_expr_unsafe(self, expr);
+        self.unsafe_depth -= 1;
+    }
+
+    fn visit_expr_unary(&mut self, expr: &'ast syn::ExprUnary) {
+        if matches!(expr.op, syn::UnOp::Deref(_)) && self.unsafe_depth > 0 && self.is_raw_pointer(&expr.expr) {
+            self.raw_derefs += 1;
+        }
+        visit::visit_expr_unary(self, expr);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        visit::visit_local(self, local);
+
+        let (name, ty) = match &local.pat {
+            Pat::Ident(pat) => (pat.ident.to_string(), None),
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat) => (pat.ident.to_string(), Some(&*pat_type.ty)),
+                _ => return,
+            },
+            _ => return,
+        };
+        let is_raw_pointer = matches!(ty, Some(Type::Ptr(_)))
+            || local.init.as_ref().is_some_and(|init| self.is_raw_pointer(&init.expr));
+        // A later `let` of the same name shadows the pointer
+        if is_raw_pointer {
+            self.raw_pointers.insert(name);
+        } else {
+            self.raw_pointers.remove(&name);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // As for calls, look into macros whose body is a list of expressions
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // Unsafe code of a nested item is audited on that item, not on this body
+    }
+}
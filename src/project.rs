@@ -1,7 +1,33 @@
-use std::collections::HashMap;
+mod audit;
+mod cargo;
+mod dead_code;
+mod docs;
+mod errors;
+mod files;
+mod loading;
+mod modules;
+mod resolve;
+mod testing;
+
+pub use audit::UnsafeAudit;
+pub use cargo::{Package, Target};
+pub use dead_code::{allows_dead_code, DeadCode};
+pub use docs::DocCoverage;
+pub use errors::ErrorSource;
+pub use files::{FileFilter, CONFIG_FILE};
+pub use loading::{LoadEvent, LoadPhase, LoadProgress, ProjectLoader};
+pub use modules::{CrateRoot, TargetKind};
+pub use testing::TestCoverage;
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
-use walkdir::WalkDir;
-use crate::parser::{parse_file, ElementType, RelationshipType};
+use rayon::prelude::*;
+use loading::LoadMonitor;
+use crate::parser::{mentioned_features, module_path_for_file, parse_file, CfgSet, ParsedFile, ElementDetails, FunctionMetrics, ElementType, ModuleDecl, Visibility, ReceiverKind, RelationshipType, SourceSpan, SyntaxError, TypeShape};
+
+/// Files read or parsed in parallel between two checks for cancellation and
+/// two progress reports.
+const LOAD_CHUNK_SIZE: usize = 128;
 
 #[derive(Default)]
 pub struct Project {
@@ -10,6 +36,35 @@ pub struct Project {
     pub relationships: Vec<Relationship>,
     pub project_path: Option<String>,
     pub file_contents: HashMap<String, String>,
+    /// Files that no `mod` declaration reaches from a crate root
+    pub orphan_files: Vec<String>,
+    /// Configuration `#[cfg]` attributes are evaluated against; loading
+    /// again applies a changed set
+    pub cfg: CfgSet,
+    /// Include and exclude globs for the project's files; loading again
+    /// applies changed globs
+    pub file_filter: FileFilter,
+    /// Files only declared by modules the cfg set switches off, e.g. another
+    /// platform's modules
+    pub cfg_disabled_files: Vec<String>,
+    /// Every feature some `feature = "..."` predicate mentions
+    pub cfg_features: BTreeSet<String>,
+    /// Files or items that couldn't be read or parsed during the last load
+    pub diagnostics: Vec<Diagnostic>,
+    /// Unsafe elements and their transitive callers
+    pub unsafe_audit: UnsafeAudit,
+    /// Tests and the production functions they exercise
+    pub test_coverage: TestCoverage,
+    /// Share of documented public items, per module
+    pub doc_coverage: Vec<DocCoverage>,
+    /// Packages of the Cargo workspace; empty for a directory without a
+    /// manifest
+    pub packages: Vec<Package>,
+    /// Module trees of the packages' targets, or of the targets found in the
+    /// file layout without a manifest
+    pub crate_roots: Vec<CrateRoot>,
+    /// Elements no entry point of the targets reaches
+    pub dead_code: DeadCode,
 }
 
 impl Project {
@@ -17,52 +72,325 @@ impl Project {
         self.file_contents.get(file_path).map(|s| s.as_str())
     }
     
+    /// The Cargo package an element belongs to, for grouping elements by
+    /// crate; `None` without a manifest, or for external items.
+    pub fn package_of(&self, element: &Element) -> Option<&str> {
+        self.crate_root_of(&element.module_path)
+            .map(|root| root.package.as_str())
+            .filter(|package| !package.is_empty())
+    }
+    
+    /// The target whose module tree a module belongs to.
+    pub fn crate_root_of(&self, module_path: &str) -> Option<&CrateRoot> {
+        self.crate_roots.iter()
+            .filter(|root| {
+                module_path == root.module_path
+                    || module_path.strip_prefix(root.module_path.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|root| root.module_path.len())
+    }
+    
     pub fn load_project(&mut self, path: &str) {
+        self.load(path, &mut LoadMonitor::default());
+    }
+    
+    /// Loads the project at `path`, telling the monitor how far it got.
+    /// Returns `false` when the monitor cancelled the load. The project is
+    /// then partly loaded and only fit to be thrown away; the app goes back
+    /// to the project it had before.
+    fn load(&mut self, path: &str, monitor: &mut LoadMonitor) -> bool {
         self.project_path = Some(path.to_string());
         self.files.clear();
         self.file_contents.clear();
         self.elements.clear();
         self.relationships.clear();
+        self.orphan_files.clear();
+        self.cfg_disabled_files.clear();
+        self.cfg_features.clear();
+        self.diagnostics.clear();
 
-        // Walk through the directory and find Rust files
-        for entry in WalkDir::new(path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-        {
-            let file_path = entry.path().to_str().unwrap().to_string();
-            let relative_path = file_path.strip_prefix(path).unwrap_or(&file_path).to_string();
-            let normalized_path = relative_path.replace('\\', "/");
-            
-            self.files.push(normalized_path.clone());
-            
-            // Read file content
-            if let Ok(content) = fs::read_to_string(&file_path) {
-                self.file_contents.insert(normalized_path.clone(), content.clone());
+        // Find the Rust files the ignore files and the filter leave
+        let (file_paths, diagnostics) = files::find_rust_files(path, &self.file_filter, monitor);
+        if monitor.is_cancelled() {
+            return false;
+        }
+        self.diagnostics.extend(diagnostics);
+        self.files = file_paths.iter()
+            .map(|file_path| file_path.strip_prefix(path).unwrap_or(file_path).replace('\\', "/"))
+            .collect();
+        monitor.files_found(&self.files);
+        
+        // Read file contents in parallel, a chunk at a time so progress and
+        // cancellation keep up
+        monitor.phase(LoadPhase::Reading);
+        let normalized_paths = self.files.clone();
+        for (chunk, normalized_chunk) in file_paths.chunks(LOAD_CHUNK_SIZE).zip(normalized_paths.chunks(LOAD_CHUNK_SIZE)) {
+            if monitor.is_cancelled() {
+                return false;
+            }
+            let contents: Vec<_> = chunk.par_iter()
+                .map(|file_path| fs::read_to_string(file_path).map(|content| {
+                    let features = mentioned_features(&content);
+                    (content, features)
+                }))
+                .collect();
+            for (normalized_path, content) in normalized_chunk.iter().zip(contents) {
+                match content {
+                    Ok((content, features)) => {
+                        self.cfg_features.extend(features);
+                        self.file_contents.insert(normalized_path.clone(), content);
+                    },
+                    Err(error) => self.diagnostics.push(Diagnostic::read_error(normalized_path.clone(), error.to_string())),
+                }
+                monitor.file_read();
+            }
+        }
+        
+        self.packages = cargo::read_workspace(path, &self.files);
+        if monitor.is_cancelled() {
+            return false;
+        }
+        monitor.phase(LoadPhase::Parsing);
+        self.load_module_tree(monitor);
+        if monitor.is_cancelled() {
+            return false;
+        }
+        
+        monitor.phase(LoadPhase::Resolving);
+        let bindings = self.use_bindings();
+        self.resolve_import_targets(&bindings);
+        self.resolve_type_targets(&bindings);
+        self.resolve_error_targets(&bindings);
+        self.resolve_call_targets();
+        self.add_external_traits();
+        self.audit_unsafe();
+        self.map_tests();
+        self.measure_doc_coverage();
+        self.find_dead_code();
+        true
+    }
+    
+    /// Parses the files reachable from the crate roots through `mod`
+    /// declarations, giving each the module path it really has, and links
+    /// parent modules to their children with `Contains` edges.
+    ///
+    /// Files no declaration leads to are still parsed, with a module path
+    /// guessed from their location, and listed in `orphan_files`. Without a
+    /// crate root (a loose directory of sources) every file is treated that way
+    /// but none is flagged.
+    fn load_module_tree(&mut self, monitor: &mut LoadMonitor) {
+        let roots = if self.packages.is_empty() {
+            modules::crate_roots(&self.files)
+        } else {
+            modules::package_roots(&self.packages)
+        };
+        let has_roots = !roots.is_empty();
+        self.crate_roots = roots.clone();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut disabled: VecDeque<(String, String, bool)> = VecDeque::new();
+        // A file module's visibility and outer attributes are on the `mod`
+        // declaration that leads to it; crate roots are public
+        let mut queue: VecDeque<(String, String, bool, ElementDetails)> = VecDeque::new();
+        // Integration tests are loaded whether or not `test` is on, like test
+        // modules, so the tests can always be mapped
+        for root in roots {
+            let details = ElementDetails { visibility: Visibility::Public, ..ElementDetails::default() };
+            queue.push_back((root.file_path, root.module_path, true, details));
+        }
+        
+        // The files at one depth of the module trees don't depend on each
+        // other and are parsed in parallel. Their results are added in queue
+        // order, so elements and relationships come out in the same order on
+        // every load.
+        while !queue.is_empty() {
+            let level: Vec<_> = queue.drain(..)
+                .filter(|(file_path, ..)| visited.insert(file_path.clone()))
+                .collect();
+            for chunk in level.chunks(LOAD_CHUNK_SIZE) {
+                if monitor.is_cancelled() {
+                    return;
+                }
+                let files: Vec<(&str, &str)> = chunk.iter()
+                    .map(|(file_path, module_path, ..)| (file_path.as_str(), module_path.as_str()))
+                    .collect();
+                let parsed = self.parse_files(&files);
                 
-                // Parse the file to extract code elements and relationships
-                if let Ok((elements, relationships)) = parse_file(&normalized_path, &content) {
-                    // Convert parser::CodeElement to project::Element
-                    for element in elements {
-                        self.elements.push(Element {
-                            id: element.id,
-                            name: element.name,
-                            file_path: element.file_path,
-                            element_type: element.element_type,
-                        });
-                    }
+                for ((file_path, module_path, is_root, decl_details), parsed) in chunk.iter().zip(parsed) {
+                    let Some(parsed) = parsed else {
+                        monitor.file_skipped();
+                        continue;
+                    };
+                    let Some((module_index, module_decls)) = self.add_parsed_file(file_path, parsed, monitor) else {
+                        continue;
+                    };
+                    let module = &mut self.elements[module_index];
+                    let inner_attributes = std::mem::take(&mut module.details.attributes);
+                    module.details.visibility = decl_details.visibility;
+                    module.details.attributes = decl_details.attributes.iter().cloned().chain(inner_attributes).collect();
+                    // `///` on the declaration comes before the file's own `//!`
+                    module.details.docs = match (decl_details.docs.clone(), module.details.docs.take()) {
+                        (Some(outer), Some(inner)) => Some(format!("{}\n\n{}", outer, inner)),
+                        (outer, inner) => outer.or(inner),
+                    };
                     
-                    // Convert parser::Relationship to project::Relationship
-                    for rel in relationships {
-                        self.relationships.push(Relationship {
-                            source_id: rel.source_id,
-                            target_id: rel.target_id,
-                            relationship_type: rel.relationship_type,
-                        });
+                    for decl in module_decls {
+                        let child_module = format!("{}::{}", decl.parent_path, decl.name);
+                        let child_file = modules::module_file_candidates(file_path, module_path, *is_root, &decl)
+                            .into_iter()
+                            .find(|candidate| self.file_contents.contains_key(candidate));
+                        
+                        match child_file {
+                            Some(child_file) if decl.cfg_active => {
+                                self.relationships.push(Relationship {
+                                    source_id: decl.parent_path,
+                                    target_id: child_module.clone(),
+                                    relationship_type: RelationshipType::Contains,
+                                    derived: false,
+                                });
+                                queue.push_back((child_file, child_module, false, decl.details));
+                            },
+                            Some(child_file) => disabled.push_back((child_file, child_module, false)),
+                            None => {},
+                        }
                     }
                 }
             }
         }
+        
+        // Modules the cfg set switches off aren't parsed into the project, and
+        // neither is anything declared inside them
+        while let Some((file_path, module_path, is_root)) = disabled.pop_front() {
+            if !visited.insert(file_path.clone()) {
+                continue;
+            }
+            let module_decls = self.file_contents.get(&file_path)
+                .and_then(|content| parse_file(&file_path, &module_path, content, &self.cfg).ok())
+                .map(|parsed| parsed.module_decls)
+                .unwrap_or_default();
+            for decl in module_decls {
+                let child_file = modules::module_file_candidates(&file_path, &module_path, is_root, &decl)
+                    .into_iter()
+                    .find(|candidate| self.file_contents.contains_key(candidate));
+                if let Some(child_file) = child_file {
+                    disabled.push_back((child_file, format!("{}::{}", decl.parent_path, decl.name), false));
+                }
+            }
+            self.cfg_disabled_files.push(file_path);
+            monitor.file_skipped();
+        }
+        
+        let unreached: Vec<(String, String)> = self.files.iter()
+            .filter(|file| !visited.contains(*file))
+            .map(|file| (file.clone(), module_path_for_file(file)))
+            .collect();
+        for chunk in unreached.chunks(LOAD_CHUNK_SIZE) {
+            if monitor.is_cancelled() {
+                return;
+            }
+            let files: Vec<(&str, &str)> = chunk.iter()
+                .map(|(file_path, module_path)| (file_path.as_str(), module_path.as_str()))
+                .collect();
+            let parsed = self.parse_files(&files);
+            for ((file_path, _), parsed) in chunk.iter().zip(parsed) {
+                match parsed {
+                    Some(parsed) => {
+                        self.add_parsed_file(file_path, parsed, monitor);
+                    },
+                    None => monitor.file_skipped(),
+                }
+                if has_roots {
+                    self.orphan_files.push(file_path.clone());
+                }
+            }
+        }
+    }
+    
+    /// Parses files, given with their module paths, across all cores. The
+    /// results come back in the order of `files`; `None` for a file that
+    /// couldn't be read.
+    fn parse_files(&self, files: &[(&str, &str)]) -> Vec<Option<Result<ParsedFile, SyntaxError>>> {
+        files.par_iter()
+            .map(|(file_path, module_path)| {
+                let content = self.file_contents.get(*file_path)?;
+                Some(parse_file(file_path, module_path, content, &self.cfg))
+            })
+            .collect()
+    }
+    
+    /// Adds a node for every trait outside the project that a type derives,
+    /// so derived impls show up in the graph like hand-written ones.
+    fn add_external_traits(&mut self) {
+        let mut known: HashSet<String> = self.elements.iter().map(|e| e.id.clone()).collect();
+        let external: Vec<String> = self.relationships.iter()
+            .filter(|rel| rel.derived && !known.contains(&rel.target_id))
+            .map(|rel| rel.target_id.clone())
+            .collect();
+        
+        for trait_path in external {
+            if !known.insert(trait_path.clone()) {
+                continue;
+            }
+            let (module_path, name) = trait_path.rsplit_once("::").unwrap_or(("", trait_path.as_str()));
+            self.elements.push(Element {
+                id: trait_path.clone(),
+                name: name.to_string(),
+                file_path: String::new(),
+                module_path: module_path.to_string(),
+                element_type: ElementType::ExternalTrait,
+                span: SourceSpan::default(),
+                receiver: None,
+                shape: None,
+                metrics: None,
+                details: ElementDetails { visibility: Visibility::Public, ..ElementDetails::default() },
+            });
+        }
+    }
+    
+    /// Adds the elements and relationships of a parsed file to the project,
+    /// returning the index of the file's module element, which the parser
+    /// emits first, and the file's `mod` declarations.
+    fn add_parsed_file(&mut self, file_path: &str, parsed: Result<ParsedFile, SyntaxError>, monitor: &mut LoadMonitor) -> Option<(usize, Vec<ModuleDecl>)> {
+        let (elements_before, relationships_before) = (self.elements.len(), self.relationships.len());
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                self.diagnostics.push(Diagnostic::syntax_error(file_path, error));
+                monitor.file_parsed(&[], &[]);
+                return None;
+            },
+        };
+        for error in parsed.errors {
+            self.diagnostics.push(Diagnostic::syntax_error(file_path, error));
+        }
+        
+        // Convert parser::CodeElement to project::Element
+        for element in parsed.elements {
+            self.elements.push(Element {
+                id: element.id,
+                name: element.name,
+                file_path: element.file_path,
+                module_path: element.module_path,
+                element_type: element.element_type,
+                span: element.span,
+                receiver: element.receiver,
+                shape: element.shape,
+                metrics: element.metrics,
+                details: element.details,
+            });
+        }
+        
+        // Convert parser::Relationship to project::Relationship
+        for rel in parsed.relationships {
+            self.relationships.push(Relationship {
+                source_id: rel.source_id,
+                target_id: rel.target_id,
+                relationship_type: rel.relationship_type,
+                derived: rel.derived,
+            });
+        }
+        monitor.file_parsed(&self.elements[elements_before..], &self.relationships[relationships_before..]);
+        
+        Some((elements_before, parsed.module_decls))
     }
 }
 
@@ -71,8 +399,63 @@ pub struct Element {
     pub id: String,
     pub name: String,
     pub file_path: String,
+    /// Module the element is declared in; for a module, its own path
+    pub module_path: String,
     #[allow(dead_code)]
     pub element_type: ElementType,
+    pub span: SourceSpan,
+    pub receiver: Option<ReceiverKind>,
+    pub shape: Option<TypeShape>,
+    pub metrics: Option<FunctionMetrics>,
+    pub details: ElementDetails,
+}
+
+/// A problem found while loading the project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file_path: String,
+    /// 1-based position of the problem; 0 when it concerns the whole file
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Read,   // The file couldn't be read, e.g. it isn't UTF-8
+    Syntax, // The file or one of its items couldn't be parsed
+}
+
+impl Diagnostic {
+    fn read_error(file_path: String, message: String) -> Self {
+        Self {
+            file_path,
+            line: 0,
+            column: 0,
+            message,
+            kind: DiagnosticKind::Read,
+        }
+    }
+    
+    fn syntax_error(file_path: &str, error: SyntaxError) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            line: error.line,
+            column: error.column,
+            message: error.message,
+            kind: DiagnosticKind::Syntax,
+        }
+    }
+    
+    /// `file:line:column`, or just the file when there is no position.
+    pub fn location(&self) -> String {
+        if self.line == 0 {
+            self.file_path.clone()
+        } else {
+            format!("{}:{}:{}", self.file_path, self.line, self.column)
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -80,4 +463,6 @@ pub struct Relationship {
     pub source_id: String,
     pub target_id: String,
     pub relationship_type: RelationshipType,
+    /// Generated by `#[derive]`; see `parser::Relationship::derived`
+    pub derived: bool,
 }
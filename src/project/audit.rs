@@ -0,0 +1,45 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::Project;
+use crate::parser::RelationshipType;
+
+/// Where a project uses `unsafe`, for security review.
+#[derive(Debug, Default)]
+pub struct UnsafeAudit {
+    /// Elements declared `unsafe` (functions, impls, traits, foreign items) or
+    /// with unsafe blocks or raw-pointer dereferences in their body
+    pub unsafe_elements: HashSet<String>,
+    /// Elements that reach an unsafe element through calls or spawned tasks,
+    /// directly or transitively, without containing unsafe code themselves
+    pub callers: HashSet<String>,
+}
+
+impl Project {
+    /// Finds the unsafe elements and walks the resolved `Calls` and `Spawns`
+    /// edges backwards from them. Runs after call resolution.
+    pub(super) fn audit_unsafe(&mut self) {
+        let unsafe_elements: HashSet<String> = self.elements.iter()
+            .filter(|element| element.details.contains_unsafe())
+            .map(|element| element.id.clone())
+            .collect();
+        
+        let mut callers_of: HashMap<&str, Vec<&str>> = HashMap::new();
+        for rel in &self.relationships {
+            if matches!(rel.relationship_type, RelationshipType::Calls | RelationshipType::Spawns) {
+                callers_of.entry(rel.target_id.as_str()).or_default().push(rel.source_id.as_str());
+            }
+        }
+        
+        let mut callers: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<&str> = unsafe_elements.iter().map(String::as_str).collect();
+        while let Some(callee) = queue.pop_front() {
+            for &caller in callers_of.get(callee).into_iter().flatten() {
+                if !unsafe_elements.contains(caller) && callers.insert(caller.to_string()) {
+                    queue.push_back(caller);
+                }
+            }
+        }
+        
+        self.unsafe_audit = UnsafeAudit { unsafe_elements, callers };
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use super::modules::{self, TargetKind};
+
+/// A package of the project's Cargo workspace.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    /// Directory of its `Cargo.toml`, relative to the project; empty for the
+    /// package at the project root
+    pub dir: String,
+    pub targets: Vec<Target>,
+}
+
+impl Package {
+    /// The name other crates `use` it by, with `-` turned into `_`.
+    pub fn crate_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+}
+
+/// Something a package builds: its library, a binary, an example, an
+/// integration test or a benchmark.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub name: String,
+    pub kind: TargetKind,
+    /// Root source file, relative to the project like `Project::files`
+    pub src_path: String,
+}
+
+/// Finds the packages of the workspace the project directory holds, and
+/// their targets.
+///
+/// `cargo metadata` knows best, so it is asked first. Without a `cargo` on
+/// the path, or offline with dependencies missing, the manifests are read
+/// directly: the root `Cargo.toml`, the workspace members it lists, and for
+/// each package the targets it declares plus those Cargo discovers from the
+/// file layout. A directory without a manifest has no packages.
+pub fn read_workspace(project_dir: &str, files: &[String]) -> Vec<Package> {
+    cargo_metadata(project_dir, files).unwrap_or_else(|| read_manifests(project_dir, files))
+}
+
+fn cargo_metadata(project_dir: &str, files: &[String]) -> Option<Vec<Package>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps", "--offline"])
+        .current_dir(project_dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let project_dir = fs::canonicalize(project_dir).ok()?;
+
+    let relative = |path: &str| -> Option<String> {
+        let path = Path::new(path).strip_prefix(&project_dir).ok()?;
+        Some(path.to_string_lossy().replace('\\', "/"))
+    };
+
+    let mut packages = Vec::new();
+    for package in metadata["packages"].as_array()? {
+        let manifest_dir = package["manifest_path"].as_str().and_then(|path| Path::new(path).parent());
+        // Members outside the opened directory can't be shown
+        let Some(dir) = manifest_dir.and_then(|dir| relative(&dir.to_string_lossy())) else {
+            continue;
+        };
+        let targets = package["targets"].as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|target| {
+                let kind = target["kind"].as_array()?.iter().find_map(|kind| target_kind(kind.as_str()?))?;
+                let src_path = project_file(files, &relative(target["src_path"].as_str()?)?)?;
+                Some(Target {
+                    name: target["name"].as_str()?.to_string(),
+                    kind,
+                    src_path,
+                })
+            })
+            .collect();
+        packages.push(Package {
+            name: package["name"].as_str()?.to_string(),
+            dir,
+            targets,
+        });
+    }
+    Some(packages)
+}
+
+/// Maps the target kinds of `cargo metadata` to ours; build scripts have
+/// none.
+fn target_kind(kind: &str) -> Option<TargetKind> {
+    match kind {
+        "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro" => Some(TargetKind::Library),
+        "bin" => Some(TargetKind::Binary),
+        "example" => Some(TargetKind::Example),
+        "test" => Some(TargetKind::Test),
+        "bench" => Some(TargetKind::Bench),
+        _ => None,
+    }
+}
+
+fn read_manifests(project_dir: &str, files: &[String]) -> Vec<Package> {
+    let Some(root) = read_manifest(Path::new(project_dir)) else {
+        return Vec::new();
+    };
+
+    let mut packages = Vec::new();
+    if let Some(package) = manifest_package(&root, "", files) {
+        packages.push(package);
+    }
+    let workspace = root.get("workspace");
+    let excluded: Vec<&str> = workspace
+        .and_then(|workspace| workspace.get("exclude"))
+        .and_then(|exclude| exclude.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| dir.as_str())
+        .collect();
+    let members = workspace
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|member| member.as_str());
+    for member in members {
+        for dir in expand_member(project_dir, member) {
+            if excluded.contains(&dir.as_str()) || packages.iter().any(|p: &Package| p.dir == dir) {
+                continue;
+            }
+            let manifest = read_manifest(&Path::new(project_dir).join(&dir));
+            if let Some(package) = manifest.and_then(|manifest| manifest_package(&manifest, &dir, files)) {
+                packages.push(package);
+            }
+        }
+    }
+    packages
+}
+
+fn read_manifest(dir: &Path) -> Option<toml::Table> {
+    fs::read_to_string(dir.join("Cargo.toml")).ok()?.parse().ok()
+}
+
+/// Lists the member directories a `members` entry names. Globs are only
+/// supported as a whole last segment, as in `crates/*`.
+fn expand_member(project_dir: &str, member: &str) -> Vec<String> {
+    let member = member.trim_end_matches('/');
+    match member.strip_suffix("/*").or((member == "*").then_some("")) {
+        Some(parent) => {
+            let Ok(entries) = fs::read_dir(Path::new(project_dir).join(parent)) else {
+                return Vec::new();
+            };
+            let mut dirs: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().join("Cargo.toml").is_file())
+                .map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if parent.is_empty() { name } else { format!("{}/{}", parent, name) }
+                })
+                .collect();
+            dirs.sort();
+            dirs
+        },
+        None => vec![member.to_string()],
+    }
+}
+
+/// Reads the `[package]` of a manifest and its targets: those the manifest
+/// declares, then those Cargo discovers in the usual places.
+fn manifest_package(manifest: &toml::Table, dir: &str, files: &[String]) -> Option<Package> {
+    let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+    let package_path = |path: &str| if dir.is_empty() { path.to_string() } else { format!("{}/{}", dir, path) };
+
+    let mut targets: Vec<Target> = Vec::new();
+    let declared = [
+        ("lib", TargetKind::Library),
+        ("bin", TargetKind::Binary),
+        ("example", TargetKind::Example),
+        ("test", TargetKind::Test),
+        ("bench", TargetKind::Bench),
+    ];
+    for (section, kind) in declared {
+        let entries: Vec<&toml::Table> = match manifest.get(section) {
+            Some(toml::Value::Table(table)) => vec![table],
+            Some(toml::Value::Array(array)) => array.iter().filter_map(|entry| entry.as_table()).collect(),
+            _ => Vec::new(),
+        };
+        for entry in entries {
+            let Some(src_path) = entry.get("path").and_then(|path| path.as_str()) else {
+                continue;
+            };
+            let Some(src_path) = project_file(files, &package_path(src_path)) else {
+                continue;
+            };
+            let target_name = entry.get("name").and_then(|name| name.as_str()).unwrap_or(&name);
+            targets.push(Target { name: target_name.replace('-', "_"), kind, src_path });
+        }
+    }
+
+    // Discovered targets, unless one was already declared for the same file
+    let prefix = package_path("");
+    let package_files: Vec<String> = files.iter()
+        .filter_map(|file| file.trim_start_matches('/').strip_prefix(prefix.as_str()).map(str::to_string))
+        .collect();
+    for root in modules::crate_roots(&package_files) {
+        let Some(src_path) = project_file(files, &package_path(&root.file_path)) else {
+            continue;
+        };
+        if targets.iter().any(|target| target.src_path == src_path) {
+            continue;
+        }
+        let is_main = root.file_path.trim_start_matches('/').trim_start_matches("src/") == "main.rs";
+        let target_name = match root.kind {
+            TargetKind::Library => name.replace('-', "_"),
+            TargetKind::Binary if is_main => name.clone(),
+            _ => root.module_path.rsplit("::").next().unwrap_or(&root.module_path).to_string(),
+        };
+        targets.push(Target { name: target_name, kind: root.kind, src_path });
+    }
+
+    Some(Package { name, dir: dir.to_string(), targets })
+}
+
+/// The entry of `files` for a path relative to the project, which may lack
+/// the leading `/` project files have.
+fn project_file(files: &[String], path: &str) -> Option<String> {
+    files.iter().find(|file| file.trim_start_matches('/') == path).cloned()
+}
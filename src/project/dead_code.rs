@@ -0,0 +1,188 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use super::modules::TargetKind;
+use super::{Element, Project};
+use crate::parser::{ElementType, RelationshipType, Visibility};
+
+/// Elements nothing reaches from the entry points of the crate's targets.
+#[derive(Debug, Default)]
+pub struct DeadCode {
+    /// `main` of binaries and examples, the public API of the library except
+    /// for `#[allow(dead_code)]` items, tests, benchmark functions and
+    /// `#[no_mangle]` exports
+    pub entry_points: Vec<String>,
+    /// Sorted by ID, so items of a module stay together
+    pub unreachable: BTreeSet<String>,
+}
+
+impl Project {
+    /// Walks the resolved relationships from every entry point and collects
+    /// the functions, types and traits never reached. Runs after call
+    /// resolution and test mapping.
+    ///
+    /// Calls, spawned tasks, type usage and imports all keep their target
+    /// alive; a reached type keeps its impl blocks alive, and with them the
+    /// methods of trait impls, which are called through the trait. A method
+    /// call the project couldn't resolve (`item.method()` on a value of
+    /// unknown type) keeps every function of that name alive rather than guess.
+    ///
+    /// Constants, statics and macros aren't judged, since their uses inside
+    /// expressions aren't recorded. A loose directory of sources has no
+    /// targets and so no entry points, and nothing is reported.
+    pub(super) fn find_dead_code(&mut self) {
+        let entry_points = self.entry_points();
+        if entry_points.is_empty() {
+            self.dead_code = DeadCode::default();
+            return;
+        }
+
+        let kinds: HashMap<&str, &ElementType> = self.elements.iter()
+            .map(|e| (e.id.as_str(), &e.element_type))
+            .collect();
+        let mut functions_by_name: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut traits_by_name: HashMap<&str, Vec<&str>> = HashMap::new();
+        for element in &self.elements {
+            if element.element_type.is_function() {
+                functions_by_name.entry(element.name.as_str()).or_default().push(element.id.as_str());
+            } else if element.element_type == ElementType::Trait {
+                traits_by_name.entry(element.name.as_str()).or_default().push(element.id.as_str());
+            }
+        }
+        // Impl blocks are named `impl Trait for Type`
+        let trait_impls: HashMap<&str, &str> = self.elements.iter()
+            .filter(|e| e.element_type == ElementType::Impl)
+            .filter_map(|e| {
+                let (trait_name, _) = e.name.strip_prefix("impl ")?.split_once(" for ")?;
+                Some((e.id.as_str(), trait_name))
+            })
+            .collect();
+
+        let mut edges: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (&impl_id, trait_name) in &trait_impls {
+            edges.entry(impl_id).or_default().extend(traits_by_name.get(trait_name).into_iter().flatten());
+        }
+        for rel in &self.relationships {
+            let source = rel.source_id.as_str();
+            let target = rel.target_id.as_str();
+            let source_kind = kinds.get(source).copied();
+            let target_kind = kinds.get(target).copied();
+            match rel.relationship_type {
+                // A path the resolver rejected, such as `Vec::new`, names a
+                // function outside the project and keeps nothing alive
+                RelationshipType::Calls | RelationshipType::Spawns if target_kind.is_none() => {
+                    if let Some(method) = target.strip_prefix('.') {
+                        edges.entry(source).or_default().extend(functions_by_name.get(method).into_iter().flatten());
+                    }
+                },
+                RelationshipType::Contains => match (source_kind, target_kind) {
+                    // A module keeps its child modules alive so their imports
+                    // count, but not its other items
+                    (Some(ElementType::Module), Some(ElementType::Module)) => edges.entry(source).or_default().push(target),
+                    // Types declared in a function body are used by the body,
+                    // which isn't recorded
+                    (Some(kind), Some(target_kind)) if kind.is_function() && target_kind.is_type() => {
+                        edges.entry(source).or_default().push(target);
+                    },
+                    (Some(ElementType::Trait), _) => {
+                        edges.entry(source).or_default().push(target);
+                        edges.entry(target).or_default().push(source);
+                    },
+                    // Inherent impls are alive while their type is; their
+                    // methods only when called
+                    (Some(ElementType::Impl), Some(kind)) if kind.is_type() => edges.entry(target).or_default().push(source),
+                    (Some(ElementType::Impl), _) if trait_impls.contains_key(source) => edges.entry(source).or_default().push(target),
+                    _ => {},
+                },
+                RelationshipType::Implements => {
+                    edges.entry(source).or_default().push(target);
+                    edges.entry(target).or_default().push(source);
+                },
+                _ => edges.entry(source).or_default().push(target),
+            }
+        }
+
+        let mut reached: HashSet<&str> = entry_points.iter().map(String::as_str).collect();
+        let mut queue: VecDeque<&str> = reached.iter().copied().collect();
+        while let Some(element) = queue.pop_front() {
+            for &target in edges.get(element).into_iter().flatten() {
+                if reached.insert(target) {
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        // Orphan files aren't compiled at all and are listed on their own
+        let orphan_files: HashSet<&str> = self.orphan_files.iter().map(String::as_str).collect();
+        let unreachable = self.elements.iter()
+            .filter(|e| is_judged(e) && !reached.contains(e.id.as_str()) && !orphan_files.contains(e.file_path.as_str()))
+            .map(|e| e.id.clone())
+            .collect();
+        self.dead_code = DeadCode { entry_points, unreachable };
+    }
+
+    fn entry_points(&self) -> Vec<String> {
+        let mut entry_points: Vec<String> = Vec::new();
+        let in_target = |element: &Element, root: &str| {
+            element.module_path == root || element.module_path.strip_prefix(root).is_some_and(|rest| rest.starts_with("::"))
+        };
+        let module_visibility: HashMap<&str, Visibility> = self.elements.iter()
+            .filter(|e| e.element_type == ElementType::Module)
+            .map(|e| (e.id.as_str(), e.details.visibility))
+            .collect();
+        // Public, and declared in modules that are all public up to the root;
+        // items of a function body are never reachable from outside
+        let is_exported = |element: &Element| {
+            let mut module = element.module_path.as_str();
+            if !module_visibility.contains_key(module) {
+                return false;
+            }
+            loop {
+                if module_visibility.get(module).is_some_and(|visibility| *visibility != Visibility::Public) {
+                    return false;
+                }
+                match module.rsplit_once("::") {
+                    Some((parent, _)) => module = parent,
+                    None => return element.details.visibility == Visibility::Public,
+                }
+            }
+        };
+
+        for root in &self.crate_roots {
+            entry_points.push(root.module_path.clone());
+            let root_path = root.module_path.as_str();
+            match root.kind {
+                TargetKind::Binary | TargetKind::Example => entry_points.push(format!("{}::main", root_path)),
+                // Items that silence the lint admit they may be unused, so they
+                // only count when something else reaches them
+                TargetKind::Library => entry_points.extend(self.elements.iter()
+                    .filter(|e| in_target(e, root_path) && is_exported(e) && !allows_dead_code(e))
+                    .map(|e| e.id.clone())),
+                // Benchmark harnesses like criterion register their functions
+                // through macros the project can't follow
+                TargetKind::Bench => entry_points.extend(self.elements.iter()
+                    .filter(|e| e.element_type.is_function() && in_target(e, root_path))
+                    .map(|e| e.id.clone())),
+                TargetKind::Test => {},
+            }
+        }
+        entry_points.extend(self.test_coverage.tests.iter().cloned());
+        entry_points.extend(self.elements.iter()
+            .filter(|e| e.details.attributes.iter().any(|attr| attr.starts_with("no_mangle") || attr.starts_with("export_name")))
+            .map(|e| e.id.clone()));
+        let ids: HashSet<&str> = self.elements.iter().map(|e| e.id.as_str()).collect();
+        entry_points.retain(|id| ids.contains(id.as_str()));
+        entry_points
+    }
+}
+
+/// Whether an element carries `#[allow(dead_code)]`.
+pub fn allows_dead_code(element: &Element) -> bool {
+    element.details.attributes.iter().any(|attr| attr.starts_with("allow") && attr.contains("dead_code"))
+}
+
+/// Elements worth reporting when unreachable: functions, types and traits
+/// of the project.
+fn is_judged(element: &Element) -> bool {
+    !element.file_path.is_empty()
+        && (element.element_type.is_function() || element.element_type.is_type())
+}
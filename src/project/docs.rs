@@ -0,0 +1,62 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::{Element, Project};
+use crate::parser::{ElementType, Visibility};
+
+/// How many of a module's public items are documented.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocCoverage {
+    pub module: String,
+    pub documented: usize,
+    pub public: usize,
+}
+
+impl DocCoverage {
+    pub fn percent(&self) -> f32 {
+        if self.public == 0 {
+            100.0
+        } else {
+            self.documented as f32 * 100.0 / self.public as f32
+        }
+    }
+}
+
+impl Project {
+    /// Counts the documented public items of every module, like the
+    /// `missing_docs` lint would. A module counts towards its parent, except
+    /// for crate roots, which count towards themselves.
+    pub(super) fn measure_doc_coverage(&mut self) {
+        // The lint leaves the items of function bodies alone
+        let module_ids: HashSet<&str> = self.elements.iter()
+            .filter(|e| e.element_type == ElementType::Module)
+            .map(|e| e.id.as_str())
+            .collect();
+        let mut modules: BTreeMap<String, DocCoverage> = BTreeMap::new();
+        for element in self.elements.iter().filter(|e| needs_docs(e) && module_ids.contains(e.module_path.as_str())) {
+            let module = match element.element_type {
+                ElementType::Module => element.id.rsplit_once("::").map_or(element.id.as_str(), |(parent, _)| parent),
+                _ => element.module_path.as_str(),
+            };
+            let coverage = modules.entry(module.to_string()).or_insert_with(|| DocCoverage {
+                module: module.to_string(),
+                documented: 0,
+                public: 0,
+            });
+            coverage.public += 1;
+            if element.details.docs.is_some() {
+                coverage.documented += 1;
+            }
+        }
+        self.doc_coverage = modules.into_values().collect();
+    }
+}
+
+/// Public items rustdoc expects documentation on. Impl blocks and the items
+/// of trait impls are documented by their trait, and external traits and
+/// spawned tasks have no doc comments to write.
+fn needs_docs(element: &Element) -> bool {
+    let is_trait_impl_item = element.id.contains("::<") && element.id.contains(" as ");
+    element.details.visibility == Visibility::Public
+        && !is_trait_impl_item
+        && !matches!(element.element_type, ElementType::Impl | ElementType::ExternalTrait | ElementType::Task)
+}
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::Project;
+use crate::parser::RelationshipType;
+
+/// An error type that a function's callees can fail with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorSource {
+    /// Element ID of the error type, or its path as written when it comes
+    /// from another crate
+    pub error_type: String,
+    /// The nearest function found to return it
+    pub function_id: String,
+    /// Whether the error can get back to the function: it comes from a direct
+    /// callee, or every function in between returns the same error type or
+    /// one with a `From` impl for it, so `?` passes it along
+    pub reaches_caller: bool,
+}
+
+impl Project {
+    /// The error type of a function returning `Result`: the resolved target
+    /// of its `ReturnsError` relationship, or the type as written when it
+    /// has no path, like `Box<dyn Error>`.
+    pub fn error_type_of(&self, function_id: &str) -> Option<&str> {
+        self.relationships.iter()
+            .find(|r| r.relationship_type == RelationshipType::ReturnsError && r.source_id == function_id)
+            .map(|r| r.target_id.as_str())
+            .or_else(|| {
+                self.elements.iter()
+                    .find(|e| e.id == function_id)
+                    .and_then(|e| e.details.error_type.as_deref())
+            })
+    }
+
+    /// Traces the error types a function can receive from the `Result`s of
+    /// the functions it calls, following resolved `Calls` edges through
+    /// callees that return `Result` themselves. Callees that don't return
+    /// `Result` handle their errors and end the trace.
+    ///
+    /// Sources are listed nearest first, once per error type.
+    pub fn error_sources(&self, function_id: &str) -> Vec<ErrorSource> {
+        let mut error_types: HashMap<&str, &str> = self.elements.iter()
+            .filter_map(|e| Some((e.id.as_str(), e.details.error_type.as_deref()?)))
+            .collect();
+        let mut callees_of: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut conversions: HashSet<(&str, &str)> = HashSet::new();
+        for rel in &self.relationships {
+            match rel.relationship_type {
+                RelationshipType::ReturnsError => {
+                    error_types.insert(rel.source_id.as_str(), rel.target_id.as_str());
+                },
+                RelationshipType::Calls => {
+                    callees_of.entry(rel.source_id.as_str()).or_default().push(rel.target_id.as_str());
+                },
+                RelationshipType::ConvertsFrom => {
+                    conversions.insert((rel.source_id.as_str(), rel.target_id.as_str()));
+                },
+                _ => {},
+            }
+        }
+        let passes_along = |from: &str, into: &str| from == into || conversions.contains(&(into, from));
+
+        let mut sources: Vec<ErrorSource> = Vec::new();
+        // Each function is visited again at most once, when it turns out to
+        // be reachable by `?` after first being found without
+        let mut visited: HashMap<&str, bool> = HashMap::from([(function_id, true)]);
+        let mut queue: VecDeque<(&str, bool)> = VecDeque::from([(function_id, true)]);
+        while let Some((caller, caller_reached)) = queue.pop_front() {
+            for &callee in callees_of.get(caller).into_iter().flatten() {
+                let Some(&error_type) = error_types.get(callee) else {
+                    continue;
+                };
+                let reached = caller_reached
+                    && (caller == function_id || error_types.get(caller).is_some_and(|&e| passes_along(error_type, e)));
+
+                match sources.iter_mut().find(|source| source.error_type == error_type) {
+                    Some(source) => source.reaches_caller |= reached,
+                    None => sources.push(ErrorSource {
+                        error_type: error_type.to_string(),
+                        function_id: callee.to_string(),
+                        reaches_caller: reached,
+                    }),
+                }
+
+                let revisit = match visited.get(callee) {
+                    Some(&was_reached) => reached && !was_reached,
+                    None => true,
+                };
+                if revisit {
+                    visited.insert(callee, reached);
+                    queue.push_back((callee, reached));
+                }
+            }
+        }
+
+        sources
+    }
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use super::loading::LoadMonitor;
+use super::Diagnostic;
+
+/// Per-project settings file, read from the project directory when it is
+/// opened.
+pub const CONFIG_FILE: &str = ".rust-code-visualizer.toml";
+
+/// Globs choosing which `.rs` files of a project are loaded, matched against
+/// paths relative to the project such as `src/main.rs`. `*` stays within a
+/// directory and `**` spans any number of them.
+///
+/// The filter comes on top of the ignore files: `.gitignore`, `.ignore` and
+/// git's exclude files are always honoured, and `target/` directories always
+/// skipped, so including a file can't bring back one they leave out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileFilter {
+    /// When not empty, only files matching one of these are loaded
+    pub include: Vec<String>,
+    /// Files matching one of these aren't loaded
+    pub exclude: Vec<String>,
+}
+
+impl FileFilter {
+    /// Reads the `[files]` table of the project's config file; a project
+    /// without one loads every file.
+    pub fn load(project_dir: &str) -> Self {
+        let config = fs::read_to_string(Path::new(project_dir).join(CONFIG_FILE))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok());
+        let files = config.as_ref().and_then(|config| config.get("files"));
+        let globs = |key: &str| -> Vec<String> {
+            files.and_then(|files| files.get(key))
+                .and_then(|globs| globs.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|glob| glob.as_str().map(str::to_string))
+                .collect()
+        };
+        Self { include: globs("include"), exclude: globs("exclude") }
+    }
+
+    /// Writes the globs to the `[files]` table of the project's config file,
+    /// keeping whatever else it holds.
+    pub fn save(&self, project_dir: &str) -> io::Result<()> {
+        let path = Path::new(project_dir).join(CONFIG_FILE);
+        let mut config: toml::Table = match fs::read_to_string(&path) {
+            Ok(content) => content.parse().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+            Err(error) => return Err(error),
+        };
+        let mut files = toml::Table::new();
+        files.insert("include".to_string(), self.include.clone().into());
+        files.insert("exclude".to_string(), self.exclude.clone().into());
+        config.insert("files".to_string(), files.into());
+        let content = toml::to_string_pretty(&config).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, content)
+    }
+}
+
+/// Finds the `.rs` files of a project directory that the ignore files and
+/// the filter leave, sorted by path. Unreadable directories and invalid globs
+/// are reported as diagnostics. Stops early when the monitor cancels, with
+/// the files found so far.
+pub(super) fn find_rust_files(project_dir: &str, filter: &FileFilter, monitor: &mut LoadMonitor) -> (Vec<String>, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+    let include = glob_set(&filter.include, &mut diagnostics);
+    let exclude = glob_set(&filter.exclude, &mut diagnostics);
+
+    let walker = WalkBuilder::new(project_dir)
+        // Honour .gitignore even when the project isn't a git checkout
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| !is_build_dir(entry.path()))
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        if monitor.is_cancelled() {
+            break;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                diagnostics.push(Diagnostic::read_error(String::new(), error.to_string()));
+                continue;
+            },
+        };
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file())
+            || entry.path().extension().is_none_or(|ext| ext != "rs")
+        {
+            continue;
+        }
+        let Ok(relative_path) = entry.path().strip_prefix(project_dir) else {
+            continue;
+        };
+        if (!filter.include.is_empty() && !include.is_match(relative_path)) || exclude.is_match(relative_path) {
+            continue;
+        }
+        files.push(entry.path().to_string_lossy().to_string());
+        monitor.file_scanned();
+    }
+    (files, diagnostics)
+}
+
+/// Cargo's build directory, or what looks like one: any `target` directory,
+/// as a workspace member's may sit far from the manifest that places it.
+fn is_build_dir(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "target") && path.is_dir()
+}
+
+fn glob_set(globs: &[String], diagnostics: &mut Vec<Diagnostic>) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        match GlobBuilder::new(glob).literal_separator(true).build() {
+            Ok(glob) => {
+                builder.add(glob);
+            },
+            Err(error) => diagnostics.push(Diagnostic::read_error(CONFIG_FILE.to_string(), error.to_string())),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project directory of empty files, removed when dropped.
+    struct TempProject(std::path::PathBuf);
+
+    impl TempProject {
+        fn new(name: &str, files: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!("rcv-files-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            for file in files {
+                let path = dir.join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, "").unwrap();
+            }
+            Self(dir)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+
+        /// Files found with `filter`, relative to the project.
+        fn find(&self, filter: &FileFilter) -> Vec<String> {
+            let (files, diagnostics) = find_rust_files(self.path(), filter, &mut LoadMonitor::default());
+            assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+            files.iter()
+                .map(|file| file.strip_prefix(self.path()).unwrap().trim_start_matches('/').to_string())
+                .collect()
+        }
+    }
+
+    impl Drop for TempProject {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn filter(include: &[&str], exclude: &[&str]) -> FileFilter {
+        FileFilter {
+            include: include.iter().map(|glob| glob.to_string()).collect(),
+            exclude: exclude.iter().map(|glob| glob.to_string()).collect(),
+        }
+    }
+
+    const FILES: [&str; 6] = ["src/main.rs", "src/a/b.rs", "src/a/c.rs", "tests/t.rs", "target/debug/build.rs", "README.md"];
+
+    #[test]
+    fn finds_rust_files_outside_target() {
+        let project = TempProject::new("all", &FILES);
+        assert_eq!(project.find(&FileFilter::default()), ["src/a/b.rs", "src/a/c.rs", "src/main.rs", "tests/t.rs"]);
+    }
+
+    #[test]
+    fn include_globs_keep_only_matching_files() {
+        let project = TempProject::new("include", &FILES);
+        assert_eq!(project.find(&filter(&["src/**"], &[])), ["src/a/b.rs", "src/a/c.rs", "src/main.rs"]);
+        // `*` doesn't cross directories
+        assert_eq!(project.find(&filter(&["src/*.rs"], &[])), ["src/main.rs"]);
+    }
+
+    #[test]
+    fn exclude_globs_drop_matching_files() {
+        let project = TempProject::new("exclude", &FILES);
+        assert_eq!(project.find(&filter(&[], &["src/a/*", "tests/**"])), ["src/main.rs"]);
+        assert_eq!(project.find(&filter(&["src/**"], &["**/c.rs"])), ["src/a/b.rs", "src/main.rs"]);
+    }
+
+    #[test]
+    fn ignore_files_win_over_include_globs() {
+        let project = TempProject::new("ignored", &["src/main.rs", "gen/out.rs"]);
+        fs::write(project.0.join(".gitignore"), "gen/\n").unwrap();
+        assert_eq!(project.find(&filter(&["**/*.rs"], &[])), ["src/main.rs"]);
+    }
+
+    #[test]
+    fn invalid_globs_are_reported() {
+        let project = TempProject::new("invalid", &["src/main.rs"]);
+        let (files, diagnostics) = find_rust_files(project.path(), &filter(&[], &["src/[main.rs"]), &mut LoadMonitor::default());
+        assert_eq!(files.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn filter_round_trips_through_the_config_file() {
+        let project = TempProject::new("config", &[]);
+        fs::create_dir_all(&project.0).unwrap();
+        fs::write(project.0.join(CONFIG_FILE), "[other]\nkey = 1\n").unwrap();
+
+        let saved = filter(&["src/**"], &["**/generated.rs"]);
+        saved.save(project.path()).unwrap();
+        assert_eq!(FileFilter::load(project.path()), saved);
+        assert!(fs::read_to_string(project.0.join(CONFIG_FILE)).unwrap().contains("[other]"));
+    }
+}
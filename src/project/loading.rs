@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use super::{Element, FileFilter, Project, Relationship};
+use crate::parser::CfgSet;
+
+/// How far a load has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadProgress {
+    pub phase: LoadPhase,
+    /// `.rs` files found so far; the total once scanning is done
+    pub files_found: usize,
+    pub files_read: usize,
+    /// Files parsed, or passed over for being switched off by the cfg set or
+    /// unreadable, so that it reaches `files_found`
+    pub files_parsed: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadPhase {
+    #[default]
+    Scanning,
+    Reading,
+    Parsing,
+    /// Resolving paths and running the analyses, which need every file
+    Resolving,
+}
+
+impl LoadPhase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LoadPhase::Scanning => "Scanning",
+            LoadPhase::Reading => "Reading",
+            LoadPhase::Parsing => "Parsing",
+            LoadPhase::Resolving => "Resolving",
+        }
+    }
+}
+
+/// What a load running in the background reports back.
+pub enum LoadEvent {
+    Progress(LoadProgress),
+    /// Every file the scan found, before any is parsed
+    Files(Vec<String>),
+    /// Elements and relationships of one more parsed file, as the parser
+    /// wrote them; paths are resolved only once every file is parsed
+    Parsed {
+        elements: Vec<Element>,
+        relationships: Vec<Relationship>,
+    },
+    Finished(Box<Project>),
+    Cancelled,
+}
+
+/// A project load running on a worker thread, so the window stays
+/// responsive while a large project loads.
+pub struct ProjectLoader {
+    pub path: String,
+    pub progress: LoadProgress,
+    events: Receiver<LoadEvent>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProjectLoader {
+    /// Starts loading the project at `path` with the given cfg set and file
+    /// filter.
+    pub fn start(path: &str, cfg: CfgSet, file_filter: FileFilter) -> Self {
+        let (sender, events) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut monitor = LoadMonitor {
+            sender: Some(sender.clone()),
+            cancelled: Some(cancelled.clone()),
+            progress: LoadProgress::default(),
+        };
+        let project_path = path.to_string();
+        thread::spawn(move || {
+            let mut project = Project { cfg, file_filter, ..Project::default() };
+            let event = if project.load(&project_path, &mut monitor) {
+                LoadEvent::Finished(Box::new(project))
+            } else {
+                LoadEvent::Cancelled
+            };
+            // The loader may be gone already, if the app was closed
+            let _ = sender.send(event);
+        });
+
+        Self {
+            path: path.to_string(),
+            progress: LoadProgress::default(),
+            events,
+            cancelled,
+        }
+    }
+
+    /// Asks the worker to stop before the next chunk of files; it answers
+    /// with `LoadEvent::Cancelled`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Takes the events sent since the last call, keeping track of the
+    /// progress.
+    pub fn poll(&mut self) -> Vec<LoadEvent> {
+        let events: Vec<LoadEvent> = self.events.try_iter().collect();
+        for event in &events {
+            if let LoadEvent::Progress(progress) = event {
+                self.progress = *progress;
+            }
+        }
+        events
+    }
+}
+
+/// The loading side of a `ProjectLoader`: reports progress and partial
+/// results, and tells the load when to stop. The default reports nothing and
+/// never stops, for loading in the foreground.
+#[derive(Default)]
+pub(super) struct LoadMonitor {
+    sender: Option<Sender<LoadEvent>>,
+    cancelled: Option<Arc<AtomicBool>>,
+    progress: LoadProgress,
+}
+
+impl LoadMonitor {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    }
+
+    pub fn phase(&mut self, phase: LoadPhase) {
+        self.progress.phase = phase;
+        self.send_progress();
+    }
+
+    /// Counts one more `.rs` file found while scanning.
+    pub fn file_scanned(&mut self) {
+        self.progress.files_found += 1;
+        self.send_progress();
+    }
+
+    pub fn files_found(&mut self, files: &[String]) {
+        self.progress.files_found = files.len();
+        self.send(|| LoadEvent::Files(files.to_vec()));
+        self.send_progress();
+    }
+
+    pub fn file_read(&mut self) {
+        self.progress.files_read += 1;
+        self.send_progress();
+    }
+
+    pub fn file_parsed(&mut self, elements: &[Element], relationships: &[Relationship]) {
+        self.progress.files_parsed += 1;
+        self.send(|| LoadEvent::Parsed {
+            elements: elements.to_vec(),
+            relationships: relationships.to_vec(),
+        });
+        self.send_progress();
+    }
+
+    /// Counts a file that isn't parsed into the project.
+    pub fn file_skipped(&mut self) {
+        self.progress.files_parsed += 1;
+        self.send_progress();
+    }
+
+    fn send_progress(&self) {
+        let progress = self.progress;
+        self.send(|| LoadEvent::Progress(progress));
+    }
+
+    /// Builds the event only when someone listens.
+    fn send(&self, event: impl FnOnce() -> LoadEvent) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event());
+        }
+    }
+}
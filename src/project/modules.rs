@@ -0,0 +1,270 @@
+use super::cargo::Package;
+use crate::parser::ModuleDecl;
+
+/// The kind of Cargo target a crate root builds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetKind {
+    Library,
+    Binary,
+    Example,
+    Test,
+    Bench,
+}
+
+impl TargetKind {
+    /// Integration tests and benchmarks, which are built with the test
+    /// harness and so with `cfg(test)` on
+    pub fn is_test_only(&self) -> bool {
+        matches!(self, TargetKind::Test | TargetKind::Bench)
+    }
+    
+    /// Short name as Cargo spells it.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TargetKind::Library => "lib",
+            TargetKind::Binary => "bin",
+            TargetKind::Example => "example",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+        }
+    }
+}
+
+/// A file that starts a module tree, with the module path of its root.
+#[derive(Debug, Clone)]
+pub struct CrateRoot {
+    pub file_path: String,
+    pub module_path: String,
+    pub kind: TargetKind,
+    /// The Cargo package building it; empty without a manifest
+    pub package: String,
+}
+
+/// Finds the crate roots among the project files: `src/lib.rs`,
+/// `src/main.rs`, binaries under `src/bin/`, and the examples, integration
+/// tests and benchmarks under `examples/`, `tests/` and `benches/`. The
+/// project may have been opened at the package directory or at `src/`
+/// itself.
+///
+/// The library, or the main binary when there is no library, is rooted at
+/// `crate`. Other binaries are separate crates, so they are rooted at their
+/// own name to keep their element IDs apart from the library's; examples,
+/// integration tests and benchmarks at `examples::name`, `tests::name` and
+/// `benches::name`.
+pub fn crate_roots(files: &[String]) -> Vec<CrateRoot> {
+    let mut roots = Vec::new();
+    let mut binaries = Vec::new();
+    let mut targets = Vec::new();
+    for file in files {
+        let path = file.trim_start_matches('/');
+        let target = [("examples/", TargetKind::Example), ("tests/", TargetKind::Test), ("benches/", TargetKind::Bench)]
+            .into_iter()
+            .find_map(|(dir, kind)| Some((dir.trim_end_matches('/'), path.strip_prefix(dir)?, kind)));
+        if let Some((dir, target, kind)) = target {
+            let name = match target.split_once('/') {
+                Some((name, "main.rs")) => name,
+                Some(_) => continue,
+                None => target.strip_suffix(".rs").unwrap_or(target),
+            };
+            targets.push(CrateRoot {
+                file_path: file.clone(),
+                module_path: format!("{}::{}", dir, name),
+                kind,
+                package: String::new(),
+            });
+            continue;
+        }
+        let path = path.strip_prefix("src/").unwrap_or(path);
+        match path {
+            "lib.rs" => roots.insert(0, CrateRoot {
+                file_path: file.clone(),
+                module_path: "crate".to_string(),
+                kind: TargetKind::Library,
+                package: String::new(),
+            }),
+            "main.rs" => binaries.insert(0, (file.clone(), "main".to_string())),
+            _ => {
+                let Some(binary) = path.strip_prefix("bin/") else {
+                    continue;
+                };
+                let name = match binary.split_once('/') {
+                    Some((name, "main.rs")) => name,
+                    Some(_) => continue,
+                    None => binary.strip_suffix(".rs").unwrap_or(binary),
+                };
+                binaries.push((file.clone(), name.to_string()));
+            },
+        }
+    }
+
+    for (file_path, name) in binaries {
+        let module_path = if roots.is_empty() { "crate".to_string() } else { name };
+        roots.push(CrateRoot { file_path, module_path, kind: TargetKind::Binary, package: String::new() });
+    }
+    roots.extend(targets);
+    roots
+}
+
+/// Roots the targets of the workspace's packages, following the layout of
+/// `crate_roots` within each package.
+///
+/// A lone package keeps its library at `crate`. In a workspace of several,
+/// each library is rooted at its crate name instead, so `use other_crate::x`
+/// paths between members resolve, and the other targets of a package are
+/// nested under that name: `my_crate::tests::name`.
+pub fn package_roots(packages: &[Package]) -> Vec<CrateRoot> {
+    let single = packages.len() == 1;
+    let mut roots = Vec::new();
+    for package in packages {
+        let crate_root = if single { "crate".to_string() } else { package.crate_name() };
+        let nested = |path: String| if single { path } else { format!("{}::{}", crate_root, path) };
+        let has_library = package.targets.iter().any(|target| target.kind == TargetKind::Library);
+        // Without a library, the binary named after the package takes its place
+        let main_binary = package.targets.iter()
+            .filter(|target| target.kind == TargetKind::Binary)
+            .find(|target| target.name == package.name)
+            .or_else(|| package.targets.iter().find(|target| target.kind == TargetKind::Binary))
+            .filter(|_| !has_library);
+
+        for target in &package.targets {
+            let is_main = main_binary.is_some_and(|main| std::ptr::eq(main, target));
+            let module_path = match target.kind {
+                TargetKind::Library => crate_root.clone(),
+                TargetKind::Binary if is_main => crate_root.clone(),
+                // `src/main.rs` is rooted at `main`, as in `crate_roots`, since
+                // the binary usually shares the library's name
+                TargetKind::Binary if target.src_path.ends_with("src/main.rs") => nested("main".to_string()),
+                TargetKind::Binary => nested(target.name.clone()),
+                TargetKind::Example => nested(format!("examples::{}", target.name)),
+                TargetKind::Test => nested(format!("tests::{}", target.name)),
+                TargetKind::Bench => nested(format!("benches::{}", target.name)),
+            };
+            roots.push(CrateRoot {
+                file_path: target.src_path.clone(),
+                module_path,
+                kind: target.kind,
+                package: package.name.clone(),
+            });
+        }
+    }
+    // Libraries first, as `crate_roots` has them
+    roots.sort_by_key(|root| root.kind != TargetKind::Library);
+    roots
+}
+
+/// Lists the files a `mod` declaration may refer to, in the order rustc
+/// tries them.
+///
+/// `file_path` is the declaring file and `file_module` the module it defines;
+/// `is_root` marks crate roots, which like `mod.rs` files keep their child
+/// modules next to them rather than in a directory named after themselves.
+pub fn module_file_candidates(file_path: &str, file_module: &str, is_root: bool, decl: &ModuleDecl) -> Vec<String> {
+    let (dir, file_name) = file_path.rsplit_once('/').unwrap_or(("", file_path));
+    let stem = file_name.strip_suffix(".rs").unwrap_or(file_name);
+
+    // Inline modules between the file's module and the declaration each add
+    // a directory level
+    let inline: Vec<&str> = decl.parent_path
+        .strip_prefix(file_module)
+        .unwrap_or("")
+        .split("::")
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let mut base = dir.to_string();
+    if !is_root && stem != "mod" {
+        base = format!("{}/{}", base, stem);
+    }
+    for segment in &inline {
+        base = format!("{}/{}", base, segment);
+    }
+
+    match &decl.path_attr {
+        // Outside inline modules, `#[path]` is relative to the declaring file
+        Some(path_attr) if inline.is_empty() => vec![normalize(&format!("{}/{}", dir, path_attr))],
+        Some(path_attr) => vec![normalize(&format!("{}/{}", base, path_attr))],
+        None => vec![
+            format!("{}/{}.rs", base, decl.name),
+            format!("{}/{}/mod.rs", base, decl.name),
+        ],
+    }
+}
+
+/// Collapses `.` and `..` segments of a `/`-separated path.
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {},
+            ".." if segments.last().is_some_and(|s| !s.is_empty()) => {
+                segments.pop();
+            },
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ElementDetails;
+
+    fn decl(parent_path: &str, name: &str, path_attr: Option<&str>) -> ModuleDecl {
+        ModuleDecl {
+            parent_path: parent_path.to_string(),
+            name: name.to_string(),
+            path_attr: path_attr.map(str::to_string),
+            details: ElementDetails::default(),
+            cfg_active: true,
+        }
+    }
+
+    #[test]
+    fn crate_roots_and_mod_rs_keep_children_beside_them() {
+        assert_eq!(
+            module_file_candidates("/src/lib.rs", "crate", true, &decl("crate", "model", None)),
+            ["/src/model.rs", "/src/model/mod.rs"],
+        );
+        assert_eq!(
+            module_file_candidates("/src/model/mod.rs", "crate::model", false, &decl("crate::model", "view", None)),
+            ["/src/model/view.rs", "/src/model/view/mod.rs"],
+        );
+    }
+
+    #[test]
+    fn other_files_keep_children_in_a_directory_of_their_name() {
+        assert_eq!(
+            module_file_candidates("/src/model.rs", "crate::model", false, &decl("crate::model", "view", None)),
+            ["/src/model/view.rs", "/src/model/view/mod.rs"],
+        );
+    }
+
+    #[test]
+    fn inline_modules_add_a_directory_level() {
+        assert_eq!(
+            module_file_candidates("/src/lib.rs", "crate", true, &decl("crate::outer", "inner", None)),
+            ["/src/outer/inner.rs", "/src/outer/inner/mod.rs"],
+        );
+    }
+
+    #[test]
+    fn path_attributes_are_relative_to_the_declaring_file() {
+        assert_eq!(
+            module_file_candidates("/src/model.rs", "crate::model", false, &decl("crate::model", "sys", Some("platform/unix.rs"))),
+            ["/src/platform/unix.rs"],
+        );
+        assert_eq!(
+            module_file_candidates("/src/model/mod.rs", "crate::model", false, &decl("crate::model", "sys", Some("../sys.rs"))),
+            ["/src/sys.rs"],
+        );
+    }
+
+    #[test]
+    fn path_attributes_inside_inline_modules_start_from_their_directory() {
+        assert_eq!(
+            module_file_candidates("/src/lib.rs", "crate", true, &decl("crate::outer", "sys", Some("unix.rs"))),
+            ["/src/outer/unix.rs"],
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Element, Project, TargetKind};
+use crate::parser::{alias_error_guess, ElementType, RelationshipType};
+
+/// Re-export and `use super::x` chains longer than this are given up on; it
+/// also keeps import cycles (`a` re-exports `b` re-exports `a`) finite.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// What a module's `use` items bring into scope, keyed by module path.
+///
+/// Built from the import paths as the parser wrote them, before any of them
+/// is resolved, so it can be shared by every resolution pass.
+pub(super) struct UseBindings {
+    /// `module::name` to the module and raw path imported under that name
+    names: HashMap<String, (String, String)>,
+    /// Modules whose contents are glob-imported into a module
+    globs: HashMap<String, Vec<String>>,
+    /// Functions, which scope the items declared in their bodies, to the
+    /// module or function they are declared in
+    scopes: HashMap<String, String>,
+    /// Root module of every target, which `crate::` paths start from
+    crate_roots: Vec<String>,
+    /// Crate names of the workspace's libraries, to their root module
+    libraries: HashMap<String, String>,
+}
+
+impl Project {
+    /// Collects the names each module imports, from the raw `Imports` targets.
+    pub(super) fn use_bindings(&self) -> UseBindings {
+        let mut bindings = UseBindings {
+            names: HashMap::new(),
+            globs: HashMap::new(),
+            scopes: self.elements.iter()
+                .filter(|e| e.element_type.is_function())
+                .map(|e| (e.id.clone(), e.module_path.clone()))
+                .collect(),
+            crate_roots: self.crate_roots.iter().map(|root| root.module_path.clone()).collect(),
+            libraries: self.crate_roots.iter()
+                .filter(|root| root.kind == TargetKind::Library)
+                .filter_map(|root| {
+                    let package = self.packages.iter().find(|package| package.name == root.package)?;
+                    Some((package.crate_name(), root.module_path.clone()))
+                })
+                .collect(),
+        };
+        for rel in &self.relationships {
+            if rel.relationship_type != RelationshipType::Imports {
+                continue;
+            }
+            let module = &rel.source_id;
+            let (path, alias) = split_rename(&rel.target_id);
+            match path.strip_suffix("::*") {
+                Some(glob) => bindings.globs.entry(module.clone()).or_default().push(glob.to_string()),
+                // `use a::B as _` brings the trait's methods in without a name
+                None if alias == Some("_") => {},
+                None => {
+                    let name = alias.unwrap_or_else(|| path.rsplit("::").next().unwrap_or(path));
+                    bindings.names.insert(format!("{}::{}", module, name), (module.clone(), path.to_string()));
+                },
+            }
+        }
+        bindings
+    }
+    
+    /// Points `Imports` relationships at the element or module they import.
+    ///
+    /// Paths anchored at `crate` are looked up directly. Other paths are tried
+    /// relative to the importing module and then to the crate root, and are
+    /// kept as external targets (`std::fs`) when neither exists. Names that a
+    /// module itself imports or re-exports are followed, so `use crate::Project`
+    /// resolves through `pub use project::Project` in the crate root.
+    pub(super) fn resolve_import_targets(&mut self, bindings: &UseBindings) {
+        let ids: HashSet<&str> = self.elements.iter().map(|e| e.id.as_str()).collect();
+        
+        let resolved: Vec<_> = self.relationships.iter()
+            .enumerate()
+            .filter(|(_, rel)| rel.relationship_type == RelationshipType::Imports)
+            .filter_map(|(index, rel)| {
+                let (path, alias) = split_rename(&rel.target_id);
+                let path = path.strip_suffix("::*").unwrap_or(path);
+                match resolve_use_path(path, &rel.source_id, &ids, bindings, 0) {
+                    Some(id) => Some((index, id)),
+                    // An external item keeps its path, without the alias
+                    None => alias.map(|_| (index, path.to_string())),
+                }
+            })
+            .collect();
+        
+        for (index, target_id) in resolved {
+            self.relationships[index].target_id = target_id;
+        }
+    }
+    
+    /// Points relationships that name a type or trait (`Uses`, `References`,
+    /// `DependsOn`, `Implements`, `Extends`, `BoundedBy`, `ReturnsError`,
+    /// `ConvertsFrom`) at the project item they name.
+    ///
+    /// Type paths are looked up like `use` paths from the module of the element
+    /// that mentions them, so both imported names (`Project` after
+    /// `use crate::project::Project`) and qualified paths (`project::Element`)
+    /// resolve. Types from other crates (`String`, `egui::Color32`) are kept
+    /// as written.
+    pub(super) fn resolve_type_targets(&mut self, bindings: &UseBindings) {
+        let ids: HashSet<&str> = self.elements.iter().map(|e| e.id.as_str()).collect();
+        let type_ids: HashSet<&str> = self.elements.iter()
+            .filter(|e| e.element_type.is_type())
+            .map(|e| e.id.as_str())
+            .collect();
+        let trait_ids: HashSet<&str> = self.elements.iter()
+            .filter(|e| e.element_type == ElementType::Trait)
+            .map(|e| e.id.as_str())
+            .collect();
+        let element_modules: HashMap<&str, &str> = self.elements.iter()
+            .map(|e| (e.id.as_str(), e.module_path.as_str()))
+            .collect();
+        
+        let resolved: Vec<_> = self.relationships.iter()
+            .enumerate()
+            .filter(|(_, rel)| matches!(
+                rel.relationship_type,
+                RelationshipType::Uses
+                    | RelationshipType::References
+                    | RelationshipType::DependsOn
+                    | RelationshipType::Implements
+                    | RelationshipType::Extends
+                    | RelationshipType::BoundedBy
+                    | RelationshipType::ReturnsError
+                    | RelationshipType::ConvertsFrom
+            ) && !ids.contains(rel.target_id.as_str()))
+            .filter_map(|(index, rel)| {
+                let module = element_modules.get(rel.source_id.as_str())?;
+                let target = resolve_use_path(&rel.target_id, module, &ids, bindings, 0)?;
+                // A derive names a trait, or a derive macro that shares its
+                // name with a local type; that stays the external trait
+                let targets = if rel.derived && rel.relationship_type == RelationshipType::Implements {
+                    &trait_ids
+                } else {
+                    &type_ids
+                };
+                targets.contains(target.as_str()).then_some((index, target))
+            })
+            .collect();
+        
+        for (index, target_id) in resolved {
+            self.relationships[index].target_id = target_id;
+        }
+    }
+    
+    /// Settles the error types of `ReturnsError` and `ConvertsFrom`
+    /// relationships. Runs after `resolve_type_targets`.
+    ///
+    /// A function returning a `Result<T>` alias of the project fails with the
+    /// alias's error type; one returning an alias from elsewhere gets the
+    /// `Error` type next to it. Error types from other crates are spelled out
+    /// through the `use` items of the mentioning module, so `io::Error` after
+    /// `use std::io` matches a `From<std::io::Error>` impl.
+    pub(super) fn resolve_error_targets(&mut self, bindings: &UseBindings) {
+        let ids: HashSet<&str> = self.elements.iter().map(|e| e.id.as_str()).collect();
+        let indices: HashMap<&str, usize> = self.elements.iter()
+            .enumerate()
+            .map(|(index, e)| (e.id.as_str(), index))
+            .collect();
+        let type_ids: HashSet<&str> = self.elements.iter()
+            .filter(|e| e.element_type.is_type())
+            .map(|e| e.id.as_str())
+            .collect();
+        let element_modules: HashMap<&str, &str> = self.elements.iter()
+            .map(|e| (e.id.as_str(), e.module_path.as_str()))
+            .collect();
+        let aliases: HashMap<&str, (&str, Option<&str>)> = self.relationships.iter()
+            .filter(|r| r.relationship_type == RelationshipType::ReturnsError)
+            .filter_map(|r| {
+                let alias = &self.elements[*indices.get(r.source_id.as_str())?];
+                if alias.element_type != ElementType::TypeAlias {
+                    return None;
+                }
+                Some((alias.id.as_str(), (r.target_id.as_str(), alias.details.error_type.as_deref())))
+            })
+            .collect();
+        
+        let mut resolved = Vec::new();
+        let mut error_types = Vec::new();
+        for (index, rel) in self.relationships.iter().enumerate() {
+            if !matches!(rel.relationship_type, RelationshipType::ReturnsError | RelationshipType::ConvertsFrom) {
+                continue;
+            }
+            if let Some(&(target, error_type)) = aliases.get(rel.target_id.as_str()) {
+                resolved.push((index, target.to_string()));
+                if let (Some(&function), Some(error_type)) = (indices.get(rel.source_id.as_str()), error_type) {
+                    error_types.push((function, error_type.to_string()));
+                }
+                continue;
+            }
+            if ids.contains(rel.target_id.as_str()) {
+                continue;
+            }
+            let Some(module) = element_modules.get(rel.source_id.as_str()) else {
+                continue;
+            };
+            
+            let mut target = bindings.spell_out(&rel.target_id, module);
+            if rel.relationship_type == RelationshipType::ReturnsError && target.rsplit("::").next() == Some("Result") {
+                target = alias_error_guess(&target);
+            }
+            match resolve_use_path(&target, module, &ids, bindings, 0) {
+                Some(project_type) if type_ids.contains(project_type.as_str()) => resolved.push((index, project_type)),
+                _ => resolved.push((index, target)),
+            }
+        }
+        
+        for (index, target_id) in resolved {
+            self.relationships[index].target_id = target_id;
+        }
+        for (function, error_type) in error_types {
+            self.elements[function].details.error_type = Some(error_type);
+        }
+    }
+    
+    /// Points `Calls` relationships, and `Spawns` of functions, at project
+    /// elements where possible.
+    ///
+    /// The parser records callees as written in the source (`helper`,
+    /// `Type::method`, `.method` for unknown receivers). Each is matched by name
+    /// against the project's functions: a path that names a project type only
+    /// matches that type's methods, otherwise a match in the caller's file wins,
+    /// then the only function with that name. Anything that stays ambiguous or
+    /// unknown is kept as an external target.
+    pub(super) fn resolve_call_targets(&mut self) {
+        let mut functions_by_name: HashMap<&str, Vec<&Element>> = HashMap::new();
+        let mut type_names: HashSet<&str> = HashSet::new();
+        // Names of the project's modules and crates, which qualify calls to
+        // its functions
+        let mut module_names: HashSet<&str> = self.crate_roots.iter()
+            .flat_map(|root| root.module_path.split("::"))
+            .collect();
+        for element in &self.elements {
+            match element.element_type {
+                ref element_type if element_type.is_function() => {
+                    functions_by_name.entry(element.name.as_str()).or_default().push(element);
+                },
+                ref element_type if element_type.is_type() => {
+                    type_names.insert(element.name.as_str());
+                },
+                ElementType::Module => {
+                    module_names.insert(element.name.as_str());
+                },
+                _ => {}
+            }
+        }
+        let element_files: HashMap<&str, &str> = self.elements.iter()
+            .map(|e| (e.id.as_str(), e.file_path.as_str()))
+            .collect();
+        
+        let mut resolved = Vec::new();
+        for (index, rel) in self.relationships.iter().enumerate() {
+            if !matches!(rel.relationship_type, RelationshipType::Calls | RelationshipType::Spawns) || element_files.contains_key(rel.target_id.as_str()) {
+                continue;
+            }
+            
+            let callee = rel.target_id.as_str();
+            let method_call = callee.strip_prefix('.');
+            let (qualifier, name) = match method_call {
+                Some(method) => (None, method),
+                None => match callee.rsplit_once("::") {
+                    Some((qualifier, name)) => (Some(qualifier), name),
+                    None => (None, callee),
+                },
+            };
+            let Some(candidates) = functions_by_name.get(name) else {
+                continue;
+            };
+            let caller_file = element_files.get(rel.source_id.as_str()).copied();
+            
+            let mut candidates: Vec<&Element> = candidates.iter()
+                .copied()
+                // Recursion goes through `self.method()`, which the parser already
+                // qualified, so an unknown receiver never means the caller itself
+                .filter(|e| method_call.is_none() || e.id != rel.source_id)
+                .collect();
+            
+            if let Some(qualifier) = qualifier {
+                let head = qualifier.split("::").next().unwrap_or(qualifier);
+                let tail = qualifier.rsplit("::").next().unwrap_or(qualifier);
+                
+                if type_names.contains(tail) {
+                    // `Type::method` matches inherent and trait methods of `Type`,
+                    // including those of impls for one instantiation of it
+                    let inherent = format!("::{}::{}", tail, name);
+                    let instantiation = (format!("::{}<", tail), format!(">::{}", name));
+                    let trait_impl = format!("::<{} as ", tail);
+                    candidates.retain(|e| {
+                        e.id.ends_with(&inherent)
+                            || (e.id.contains(&instantiation.0) && e.id.ends_with(&instantiation.1))
+                            || e.id.contains(&trait_impl)
+                    });
+                } else if !matches!(head, "crate" | "self" | "super") && !module_names.contains(tail) {
+                    // `Vec::new` must not resolve to some project `new`
+                    continue;
+                }
+                
+                let qualified_suffix = format!("::{}", callee);
+                if let [qualified] = candidates.iter().filter(|e| e.id.ends_with(&qualified_suffix)).collect::<Vec<_>>()[..] {
+                    resolved.push((index, qualified.id.clone()));
+                    continue;
+                }
+            }
+            
+            // A receiver of unknown type could be anything (`v.push()`), so
+            // method calls only resolve within the caller's file
+            let same_file: Vec<_> = candidates.iter().filter(|e| Some(e.file_path.as_str()) == caller_file).collect();
+            if let [target] = same_file[..] {
+                resolved.push((index, target.id.clone()));
+            } else if let ([target], None) = (&candidates[..], method_call) {
+                resolved.push((index, target.id.clone()));
+            }
+        }
+        
+        for (index, target_id) in resolved {
+            self.relationships[index].target_id = target_id;
+        }
+    }
+}
+
+/// Splits a renamed import target, `path as Alias`, into the path and alias.
+fn split_rename(target: &str) -> (&str, Option<&str>) {
+    match target.split_once(" as ") {
+        Some((path, alias)) => (path, Some(alias)),
+        None => (target, None),
+    }
+}
+
+impl UseBindings {
+    /// The root module of the target `module` belongs to; `crate` when no
+    /// target holds it.
+    fn crate_root_of(&self, module: &str) -> &str {
+        self.crate_roots.iter()
+            .filter(|root| module == root.as_str() || module.strip_prefix(root.as_str()).is_some_and(|rest| rest.starts_with("::")))
+            .max_by_key(|root| root.len())
+            .map_or("crate", String::as_str)
+    }
+    
+    /// Replaces the first segment of a path by what `module` imports under
+    /// that name, e.g. `io::Error` by `std::io::Error` after `use std::io`.
+    fn spell_out(&self, path: &str, module: &str) -> String {
+        let (head, rest) = match path.split_once("::") {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (path, None),
+        };
+        match (self.names.get(&format!("{}::{}", module, head)), rest) {
+            (Some((_, imported)), Some(rest)) => format!("{}::{}", imported, rest),
+            (Some((_, imported)), None) => imported.clone(),
+            (None, _) => path.to_string(),
+        }
+    }
+}
+
+/// Resolves a path as written in a `use` item of `module`.
+///
+/// `crate` stands for the root of the target `module` belongs to, and the
+/// crate name of a workspace library for that library's root.
+fn resolve_use_path(path: &str, module: &str, ids: &HashSet<&str>, bindings: &UseBindings, depth: usize) -> Option<String> {
+    let crate_root = bindings.crate_root_of(module);
+    let (head, rest) = match path.split_once("::") {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (path, None),
+    };
+    let anchored = match head {
+        "crate" => Some(crate_root),
+        _ => bindings.libraries.get(head).map(String::as_str),
+    };
+    if let Some(root) = anchored {
+        let path = match rest {
+            Some(rest) => format!("{}::{}", root, rest),
+            None => root.to_string(),
+        };
+        // A module may share its name with a library of the workspace
+        if head == "crate" {
+            return lookup_path(&path, ids, bindings, depth);
+        }
+        if let Some(resolved) = lookup_path(&path, ids, bindings, depth) {
+            return Some(resolved);
+        }
+    }
+    lookup_path(&format!("{}::{}", module, path), ids, bindings, depth)
+        .or_else(|| match bindings.scopes.get(module) {
+            // Items of a function body see the names of the scopes around it
+            Some(outer) => resolve_use_path(path, outer, ids, bindings, depth),
+            None => lookup_path(&format!("{}::{}", crate_root, path), ids, bindings, depth),
+        })
+}
+
+/// Finds the element an absolute path names, following `use` bindings and
+/// glob imports of the modules along the way.
+fn lookup_path(path: &str, ids: &HashSet<&str>, bindings: &UseBindings, depth: usize) -> Option<String> {
+    if depth > MAX_IMPORT_DEPTH {
+        return None;
+    }
+    if ids.contains(path) {
+        return Some(path.to_string());
+    }
+    
+    // A prefix of the path may be a name some module imported, e.g.
+    // `crate::Project` where the crate root has `pub use project::Project`
+    let segments: Vec<&str> = path.split("::").collect();
+    for len in (2..=segments.len()).rev() {
+        let prefix = segments[..len].join("::");
+        if let Some((module, target)) = bindings.names.get(&prefix) {
+            if let Some(resolved) = resolve_use_path(target, module, ids, bindings, depth + 1) {
+                let rest = &segments[len..];
+                if rest.is_empty() {
+                    return Some(resolved);
+                }
+                return lookup_path(&format!("{}::{}", resolved, rest.join("::")), ids, bindings, depth + 1);
+            }
+        }
+    }
+    
+    let (module, name) = path.rsplit_once("::")?;
+    bindings.globs.get(module)?.iter().find_map(|glob| {
+        let glob_module = resolve_use_path(glob, module, ids, bindings, depth + 1)?;
+        lookup_path(&format!("{}::{}", glob_module, name), ids, bindings, depth + 1)
+    })
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::Project;
+use crate::parser::{ElementType, RelationshipType, Visibility};
+
+/// Which tests reach which production functions through the call graph.
+#[derive(Debug, Default)]
+pub struct TestCoverage {
+    /// `#[test]`, `#[bench]` and runtime test functions like `#[tokio::test]`
+    pub tests: Vec<String>,
+    /// Everything that only exists for testing: test functions, `#[cfg(test)]`
+    /// modules with their contents, and integration tests under `tests/`
+    pub test_code: HashSet<String>,
+    /// Production function to the tests that call it, directly or through
+    /// other functions
+    pub tested_by: HashMap<String, Vec<String>>,
+    /// Test to the production functions it calls, directly or transitively
+    pub exercises: HashMap<String, Vec<String>>,
+    /// Public production functions no test reaches
+    pub untested_public: Vec<String>,
+}
+
+impl Project {
+    /// Finds the tests and walks the resolved `Calls` and `Spawns` edges from
+    /// each. Runs after call resolution.
+    ///
+    /// Test modules and integration tests are loaded whatever the cfg set, so
+    /// the tests are found with `test` off too.
+    pub(super) fn map_tests(&mut self) {
+        let test_modules: Vec<&str> = self.elements.iter()
+            .filter(|e| {
+                e.element_type == ElementType::Module
+                    && (self.crate_roots.iter().any(|root| root.kind.is_test_only() && root.module_path == e.id)
+                        || e.details.attributes.iter().any(|attr| is_test_cfg(attr)))
+            })
+            .map(|e| e.id.as_str())
+            .collect();
+        let in_test_module = |module_path: &str| {
+            test_modules.iter().any(|module| {
+                module_path == *module || module_path.strip_prefix(module).is_some_and(|rest| rest.starts_with("::"))
+            })
+        };
+        
+        let mut coverage = TestCoverage::default();
+        for element in &self.elements {
+            let is_test = element.element_type.is_function() && element.details.attributes.iter().any(|attr| is_test_attribute(attr));
+            if is_test {
+                coverage.tests.push(element.id.clone());
+            }
+            if is_test || in_test_module(&element.module_path) {
+                coverage.test_code.insert(element.id.clone());
+            }
+        }
+        
+        let mut callees_of: HashMap<&str, Vec<&str>> = HashMap::new();
+        for rel in &self.relationships {
+            if matches!(rel.relationship_type, RelationshipType::Calls | RelationshipType::Spawns) {
+                callees_of.entry(rel.source_id.as_str()).or_default().push(rel.target_id.as_str());
+            }
+        }
+        let production_functions: HashSet<&str> = self.elements.iter()
+            .filter(|e| e.element_type.is_function() && !coverage.test_code.contains(&e.id))
+            .map(|e| e.id.as_str())
+            .collect();
+        
+        for test in &coverage.tests {
+            let mut seen: HashSet<&str> = HashSet::from([test.as_str()]);
+            let mut queue: VecDeque<&str> = VecDeque::from([test.as_str()]);
+            let mut exercised = Vec::new();
+            while let Some(caller) = queue.pop_front() {
+                for &callee in callees_of.get(caller).into_iter().flatten() {
+                    if !seen.insert(callee) {
+                        continue;
+                    }
+                    if production_functions.contains(callee) {
+                        exercised.push(callee.to_string());
+                        coverage.tested_by.entry(callee.to_string()).or_default().push(test.clone());
+                    }
+                    queue.push_back(callee);
+                }
+            }
+            coverage.exercises.insert(test.clone(), exercised);
+        }
+        
+        coverage.untested_public = self.elements.iter()
+            .filter(|e| {
+                production_functions.contains(e.id.as_str())
+                    && e.element_type != ElementType::ForeignFunction
+                    && e.details.visibility == Visibility::Public
+                    && !coverage.tested_by.contains_key(&e.id)
+            })
+            .map(|e| e.id.clone())
+            .collect();
+        
+        self.test_coverage = coverage;
+    }
+}
+
+/// Whether an attribute marks a test or benchmark entry point: `test`,
+/// `bench`, or a runtime's variant such as `tokio::test(flavor = "...")`.
+fn is_test_attribute(attribute: &str) -> bool {
+    let path = attribute.split('(').next().unwrap_or(attribute).trim();
+    matches!(path.rsplit("::").next(), Some("test" | "bench"))
+}
+
+/// Whether an attribute is a `cfg` that requires `test`, like `cfg(test)` or
+/// `cfg(all(test, feature = "x"))`.
+fn is_test_cfg(attribute: &str) -> bool {
+    attribute.starts_with("cfg(")
+        && !attribute.contains("not(")
+        && attribute.split(|c: char| !c.is_alphanumeric() && c != '_').any(|word| word == "test")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn finds_unit_tests_with_test_off() {
+        let dir = std::env::temp_dir().join(format!("rcv-testing-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"t\"\nversion = \"0.1.0\"\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "\
+pub fn double(x: u32) -> u32 { x * 2 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles() {
+        assert_eq!(double(2), 4);
+    }
+}
+").unwrap();
+
+        let mut project = Project::default();
+        project.load_project(dir.to_str().unwrap());
+        let _ = fs::remove_dir_all(&dir);
+
+        let coverage = &project.test_coverage;
+        assert_eq!(coverage.tests, ["crate::tests::doubles"]);
+        assert_eq!(coverage.tested_by["crate::double"], ["crate::tests::doubles"]);
+        assert!(coverage.test_code.contains("crate::tests"));
+        assert!(coverage.untested_public.is_empty());
+    }
+}
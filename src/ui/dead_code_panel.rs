@@ -0,0 +1,69 @@
+use eframe::egui;
+use std::collections::HashMap;
+
+use crate::app::{App, ViewMode};
+use crate::project::{allows_dead_code, Element};
+
+/// Lists the functions, types and traits no entry point of the crate's
+/// targets reaches, grouped by module.
+pub fn render(app: &mut App, ctx: &egui::Context) {
+    if !app.show_dead_code {
+        return;
+    }
+    
+    egui::SidePanel::right("dead_code_panel").default_width(280.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Dead Code");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").on_hover_text("Close").clicked() {
+                    app.show_dead_code = false;
+                }
+            });
+        });
+        ui.separator();
+        
+        let dead_code = &app.project.dead_code;
+        if dead_code.entry_points.is_empty() {
+            ui.label("No Cargo targets found (src/main.rs, src/lib.rs, src/bin, tests, examples or benches), so there are no entry points to start from.");
+            return;
+        }
+        ui.label(format!(
+            "{} unreachable elements from {} entry points",
+            dead_code.unreachable.len(),
+            dead_code.entry_points.len()
+        ));
+        ui.checkbox(&mut app.visualization_state.dead_code_only, "Only show dead code in the graph");
+        ui.weak("Calls through macros, function pointers and trait objects may be missed.");
+        ui.separator();
+        
+        let mut clicked: Option<String> = None;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            let elements: HashMap<&str, &Element> = app.project.elements.iter()
+                .filter(|e| dead_code.unreachable.contains(&e.id))
+                .map(|e| (e.id.as_str(), e))
+                .collect();
+            let mut current_module: Option<&str> = None;
+            for element in dead_code.unreachable.iter().filter_map(|id| elements.get(id.as_str())) {
+                if current_module != Some(element.module_path.as_str()) {
+                    current_module = Some(element.module_path.as_str());
+                    ui.add_space(4.0);
+                    ui.strong(&element.module_path);
+                }
+                ui.horizontal(|ui| {
+                    let label = format!("{:?} {}", element.element_type, element.name);
+                    if ui.link(label).on_hover_text(&element.id).clicked() {
+                        clicked = Some(element.id.clone());
+                    }
+                    if allows_dead_code(element) {
+                        ui.weak("#[allow(dead_code)]");
+                    }
+                });
+            }
+        });
+        
+        if let Some(element_id) = clicked {
+            app.visualization_state.selected_element = Some(element_id);
+            app.view_mode = ViewMode::Visualization;
+        }
+    });
+}
@@ -0,0 +1,50 @@
+use eframe::egui;
+use crate::app::{App, ViewMode};
+use crate::project::DiagnosticKind;
+
+/// Lists the problems found while loading the project. Clicking one opens
+/// its file in the editor.
+pub fn render(app: &mut App, ctx: &egui::Context) {
+    if !app.show_diagnostics {
+        return;
+    }
+    
+    egui::TopBottomPanel::bottom("diagnostics_panel")
+        .resizable(true)
+        .default_height(140.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(format!("Diagnostics ({})", app.project.diagnostics.len()));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✖").on_hover_text("Close").clicked() {
+                        app.show_diagnostics = false;
+                    }
+                });
+            });
+            ui.separator();
+            
+            if app.project.diagnostics.is_empty() {
+                ui.weak("Every file was read and parsed without errors.");
+                return;
+            }
+            
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                for diagnostic in &app.project.diagnostics {
+                    let (icon, color) = match diagnostic.kind {
+                        DiagnosticKind::Read => ("⛔", egui::Color32::from_rgb(220, 80, 80)),
+                        DiagnosticKind::Syntax => ("⚠", egui::Color32::from_rgb(230, 180, 80)),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(icon).color(color));
+                        let location = ui.link(diagnostic.location());
+                        ui.label(&diagnostic.message);
+                        
+                        if location.clicked() && app.project.file_contents.contains_key(&diagnostic.file_path) {
+                            app.selected_file = Some(diagnostic.file_path.clone());
+                            app.view_mode = ViewMode::Editor;
+                        }
+                    });
+                }
+            });
+        });
+}
@@ -0,0 +1,79 @@
+use eframe::egui;
+use crate::app::App;
+
+/// Shows the documentation of the selected element, and how well each module
+/// documents its public items.
+pub fn render(app: &mut App, ctx: &egui::Context) {
+    if !app.show_docs || app.project.files.is_empty() {
+        return;
+    }
+    
+    egui::SidePanel::right("docs_panel").default_width(320.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Documentation");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").on_hover_text("Close").clicked() {
+                    app.show_docs = false;
+                }
+            });
+        });
+        ui.separator();
+        
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            let selected = app.visualization_state.selected_element.as_ref()
+                .and_then(|id| app.project.elements.iter().find(|e| &e.id == id));
+            match selected {
+                Some(element) => {
+                    ui.strong(&element.name);
+                    ui.weak(format!("{:?} · {}", element.element_type, element.id));
+                    if let Some(signature) = &element.details.signature {
+                        ui.monospace(signature);
+                    }
+                    ui.separator();
+                    match &element.details.docs {
+                        Some(docs) => crate::ui::markdown::render(ui, docs),
+                        None => {
+                            ui.weak("No documentation");
+                        },
+                    }
+                },
+                None => {
+                    ui.weak("Select a node to read its documentation");
+                },
+            }
+            
+            ui.add_space(8.0);
+            egui::CollapsingHeader::new("Doc coverage of public items")
+                .default_open(selected.is_none())
+                .show(ui, |ui| doc_coverage(ui, app));
+        });
+    });
+}
+
+fn doc_coverage(ui: &mut egui::Ui, app: &App) {
+    let coverage = &app.project.doc_coverage;
+    if coverage.is_empty() {
+        ui.weak("No public items");
+        return;
+    }
+    
+    let documented: usize = coverage.iter().map(|c| c.documented).sum();
+    let public: usize = coverage.iter().map(|c| c.public).sum();
+    ui.label(format!("Overall: {} of {} documented", documented, public));
+    ui.add(egui::ProgressBar::new(documented as f32 / public as f32).show_percentage());
+    ui.separator();
+    
+    egui::Grid::new("doc_coverage").striped(true).show(ui, |ui| {
+        for module in coverage {
+            ui.label(&module.module);
+            ui.label(format!("{}/{}", module.documented, module.public));
+            let color = match module.percent() {
+                p if p >= 80.0 => egui::Color32::from_rgb(120, 210, 120),
+                p if p >= 50.0 => egui::Color32::from_rgb(230, 200, 90),
+                _ => egui::Color32::from_rgb(230, 110, 90),
+            };
+            ui.label(egui::RichText::new(format!("{:.0}%", module.percent())).color(color));
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,176 @@
+use eframe::egui;
+use egui::text::{LayoutJob, TextFormat};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+
+/// Renders the Markdown of a doc comment: paragraphs, headings, lists, code
+/// blocks, block quotes and inline emphasis, code and links. Anything more
+/// exotic (tables, HTML) shows as plain text.
+pub fn render(ui: &mut egui::Ui, markdown: &str) {
+    let mut renderer = MarkdownRenderer::new(ui);
+    for event in Parser::new(markdown) {
+        renderer.event(ui, event);
+    }
+    renderer.flush(ui);
+}
+
+struct MarkdownRenderer {
+    job: LayoutJob,
+    body_size: f32,
+    text_color: egui::Color32,
+    strong_color: egui::Color32,
+    link_color: egui::Color32,
+    code_background: egui::Color32,
+    heading: Option<HeadingLevel>,
+    emphasis: bool,
+    strong: bool,
+    link: bool,
+    quote_depth: usize,
+    /// Next number of each open list; `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    code_block: Option<String>,
+}
+
+impl MarkdownRenderer {
+    fn new(ui: &egui::Ui) -> Self {
+        let visuals = ui.visuals();
+        Self {
+            job: LayoutJob::default(),
+            body_size: egui::TextStyle::Body.resolve(ui.style()).size,
+            text_color: visuals.text_color(),
+            strong_color: visuals.strong_text_color(),
+            link_color: visuals.hyperlink_color,
+            code_background: visuals.code_bg_color,
+            heading: None,
+            emphasis: false,
+            strong: false,
+            link: false,
+            quote_depth: 0,
+            lists: Vec::new(),
+            code_block: None,
+        }
+    }
+
+    fn event(&mut self, ui: &mut egui::Ui, event: Event) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.flush(ui);
+                self.heading = Some(level);
+            },
+            Event::End(TagEnd::Heading(_)) => {
+                self.flush(ui);
+                self.heading = None;
+            },
+            Event::Start(Tag::CodeBlock(_)) => {
+                self.flush(ui);
+                self.code_block = Some(String::new());
+            },
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(code) = self.code_block.take() {
+                    egui::Frame::none()
+                        .fill(self.code_background)
+                        .inner_margin(egui::style::Margin::same(4.0))
+                        .show(ui, |ui| ui.monospace(code.trim_end()));
+                }
+            },
+            Event::Start(Tag::List(start)) => {
+                self.flush(ui);
+                self.lists.push(start);
+            },
+            Event::End(TagEnd::List(_)) => {
+                self.flush(ui);
+                self.lists.pop();
+            },
+            Event::Start(Tag::Item) => {
+                self.flush(ui);
+                let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    },
+                    _ => format!("{}• ", indent),
+                };
+                self.append(&marker, self.format());
+            },
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.flush(ui);
+                self.quote_depth += 1;
+            },
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush(ui);
+                self.quote_depth -= 1;
+            },
+            Event::Start(Tag::Emphasis) => self.emphasis = true,
+            Event::End(TagEnd::Emphasis) => self.emphasis = false,
+            Event::Start(Tag::Strong) => self.strong = true,
+            Event::End(TagEnd::Strong) => self.strong = false,
+            Event::Start(Tag::Link { .. }) => self.link = true,
+            Event::End(TagEnd::Link) => self.link = false,
+            Event::End(TagEnd::Paragraph | TagEnd::Item) => self.flush(ui),
+            Event::Text(text) => match &mut self.code_block {
+                Some(code) => code.push_str(&text),
+                None => self.append(&text, self.format()),
+            },
+            Event::Code(code) => {
+                let format = TextFormat {
+                    font_id: egui::FontId::monospace(self.body_size),
+                    background: self.code_background,
+                    ..self.format()
+                };
+                self.append(&code, format);
+            },
+            Event::SoftBreak => self.append(" ", self.format()),
+            Event::HardBreak => self.append("\n", self.format()),
+            Event::Rule => {
+                self.flush(ui);
+                ui.separator();
+            },
+            _ => {},
+        }
+    }
+
+    fn format(&self) -> TextFormat {
+        let size = match self.heading {
+            Some(HeadingLevel::H1) => self.body_size * 1.5,
+            Some(HeadingLevel::H2) => self.body_size * 1.3,
+            Some(_) => self.body_size * 1.15,
+            None => self.body_size,
+        };
+        let color = if self.link {
+            self.link_color
+        } else if self.strong || self.heading.is_some() {
+            self.strong_color
+        } else if self.quote_depth > 0 {
+            self.text_color.linear_multiply(0.7)
+        } else {
+            self.text_color
+        };
+        TextFormat {
+            font_id: egui::FontId::proportional(size),
+            color,
+            italics: self.emphasis || self.quote_depth > 0,
+            underline: if self.link { egui::Stroke::new(1.0, color) } else { egui::Stroke::NONE },
+            ..TextFormat::default()
+        }
+    }
+
+    fn append(&mut self, text: &str, format: TextFormat) {
+        if self.job.text.is_empty() && self.quote_depth > 0 {
+            self.job.append(&"▌ ".repeat(self.quote_depth), 0.0, TextFormat { color: self.text_color.linear_multiply(0.4), ..format.clone() });
+        }
+        self.job.append(text, 0.0, format);
+    }
+
+    /// Lays out the block collected so far.
+    fn flush(&mut self, ui: &mut egui::Ui) {
+        if self.job.text.is_empty() {
+            return;
+        }
+        let mut job = std::mem::take(&mut self.job);
+        job.wrap.max_width = ui.available_width();
+        ui.label(job);
+        if self.lists.is_empty() {
+            ui.add_space(4.0);
+        }
+    }
+}
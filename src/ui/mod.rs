@@ -1,6 +1,11 @@
 pub mod top_panel;
 pub mod side_panel;
 pub mod central_panel;
+pub mod dead_code_panel;
+pub mod diagnostics_panel;
+pub mod docs_panel;
+pub mod markdown;
+pub mod tests_panel;
 
 use eframe::egui;
 
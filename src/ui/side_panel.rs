@@ -1,5 +1,8 @@
 use eframe::egui;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use crate::app::App;
+use crate::project::{CrateRoot, Project};
 
 pub fn render(app: &mut App, ctx: &egui::Context) {
     egui::SidePanel::left("file_panel").show(ctx, |ui| {
@@ -9,14 +12,122 @@ pub fn render(app: &mut App, ctx: &egui::Context) {
         if app.project.files.is_empty() {
             ui.label("No project loaded.");
         } else {
+            let marks = FileMarks::of(&app.project);
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for file in &app.project.files {
-                    let is_selected = app.selected_file.as_ref() == Some(file);
-                    if ui.selectable_label(is_selected, file).clicked() {
-                        app.selected_file = Some(file.clone());
+                if app.project.packages.len() > 1 {
+                    crate_tree(ui, &app.project, &marks, &mut app.selected_file);
+                } else {
+                    for file in &app.project.files {
+                        file_entry(ui, &marks, &mut app.selected_file, file);
                     }
                 }
             });
         }
     });
 }
+
+/// Files of a workspace under a header per crate, and within it per target,
+/// since a flat list of every crate's files is hard to find anything in.
+fn crate_tree(ui: &mut egui::Ui, project: &Project, marks: &FileMarks, selected_file: &mut Option<String>) {
+    // The target a file belongs to, found through the elements it declares
+    let mut roots: HashMap<&str, &CrateRoot> = HashMap::new();
+    for element in &project.elements {
+        if roots.contains_key(element.file_path.as_str()) {
+            continue;
+        }
+        if let Some(root) = project.crate_root_of(&element.module_path) {
+            roots.insert(element.file_path.as_str(), root);
+        }
+    }
+    
+    let mut unpackaged: Vec<&String> = Vec::new();
+    let mut packages: BTreeMap<&str, BTreeMap<String, Vec<&String>>> = BTreeMap::new();
+    for file in &project.files {
+        match roots.get(file.as_str()).filter(|root| !root.package.is_empty()) {
+            Some(root) => {
+                let target = format!("{} {}", root.kind.label(), root.module_path);
+                packages.entry(root.package.as_str()).or_default().entry(target).or_default().push(file);
+            },
+            None => unpackaged.push(file),
+        }
+    }
+    
+    for (package, targets) in packages {
+        let file_count: usize = targets.values().map(Vec::len).sum();
+        egui::CollapsingHeader::new(format!("📦 {} ({} files)", package, file_count))
+            .id_source(("crate", package))
+            .show(ui, |ui| {
+                for (target, files) in targets {
+                    egui::CollapsingHeader::new(target.as_str())
+                        .id_source(("target", package, target.as_str()))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for file in files {
+                                file_entry(ui, marks, selected_file, file);
+                            }
+                        });
+                }
+            });
+    }
+    if !unpackaged.is_empty() {
+        egui::CollapsingHeader::new(format!("Other files ({})", unpackaged.len()))
+            .id_source("unpackaged_files")
+            .show(ui, |ui| {
+                for file in unpackaged {
+                    file_entry(ui, marks, selected_file, file);
+                }
+            });
+    }
+}
+
+/// What the file list flags about each file, gathered once a frame rather
+/// than once a file.
+struct FileMarks<'a> {
+    orphans: HashSet<&'a str>,
+    cfg_disabled: HashSet<&'a str>,
+    /// Diagnostic messages by file
+    diagnostics: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> FileMarks<'a> {
+    fn of(project: &'a Project) -> Self {
+        let mut diagnostics: HashMap<&str, Vec<&str>> = HashMap::new();
+        for diagnostic in &project.diagnostics {
+            diagnostics.entry(diagnostic.file_path.as_str()).or_default().push(diagnostic.message.as_str());
+        }
+        Self {
+            orphans: project.orphan_files.iter().map(String::as_str).collect(),
+            cfg_disabled: project.cfg_disabled_files.iter().map(String::as_str).collect(),
+            diagnostics,
+        }
+    }
+}
+
+fn file_entry(ui: &mut egui::Ui, marks: &FileMarks, selected_file: &mut Option<String>, file: &String) {
+    let is_selected = selected_file.as_ref() == Some(file);
+    let is_orphan = marks.orphans.contains(file.as_str());
+    let is_cfg_disabled = marks.cfg_disabled.contains(file.as_str());
+    let diagnostics: &[&str] = marks.diagnostics.get(file.as_str()).map_or(&[], Vec::as_slice);
+    
+    let label = if !diagnostics.is_empty() {
+        egui::RichText::new(format!("⛔ {} ({})", file, diagnostics.len())).color(egui::Color32::from_rgb(220, 80, 80))
+    } else if is_orphan {
+        egui::RichText::new(format!("⚠ {}", file)).color(egui::Color32::from_rgb(230, 180, 80))
+    } else if is_cfg_disabled {
+        egui::RichText::new(file).color(egui::Color32::from_gray(110)).italics()
+    } else {
+        egui::RichText::new(file)
+    };
+    
+    let mut response = ui.selectable_label(is_selected, label);
+    if !diagnostics.is_empty() {
+        response = response.on_hover_text(diagnostics.join("\n"));
+    } else if is_orphan {
+        response = response.on_hover_text("Not reachable from any crate root through `mod` declarations");
+    } else if is_cfg_disabled {
+        response = response.on_hover_text("Not compiled under the active configuration");
+    }
+    if response.clicked() {
+        *selected_file = Some(file.clone());
+    }
+}
@@ -0,0 +1,85 @@
+use eframe::egui;
+use crate::app::{App, ViewMode};
+
+/// Answers which tests exercise the selected function, and lists the public
+/// functions no test reaches.
+pub fn render(app: &mut App, ctx: &egui::Context) {
+    if !app.show_tests {
+        return;
+    }
+    
+    egui::SidePanel::right("tests_panel").default_width(260.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Tests");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").on_hover_text("Close").clicked() {
+                    app.show_tests = false;
+                }
+            });
+        });
+        ui.separator();
+        
+        let coverage = &app.project.test_coverage;
+        ui.label(format!(
+            "{} tests, {} functions exercised, {} public functions untested",
+            coverage.tests.len(),
+            coverage.tested_by.len(),
+            coverage.untested_public.len()
+        ));
+        ui.separator();
+        
+        let mut clicked: Option<String> = None;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            match app.visualization_state.selected_element.as_deref() {
+                Some(selected) if coverage.exercises.contains_key(selected) => {
+                    ui.strong(format!("{} exercises:", short_name(selected)));
+                    let exercised = &coverage.exercises[selected];
+                    if exercised.is_empty() {
+                        ui.weak("No production function");
+                    }
+                    element_links(ui, exercised, &mut clicked);
+                },
+                Some(selected) => {
+                    ui.strong(format!("Tests exercising {}:", short_name(selected)));
+                    match coverage.tested_by.get(selected) {
+                        Some(tests) => element_links(ui, tests, &mut clicked),
+                        None => {
+                            ui.weak("No test reaches this element");
+                        },
+                    }
+                },
+                None => {
+                    ui.weak("Select a function to see the tests exercising it");
+                },
+            }
+            
+            ui.separator();
+            egui::CollapsingHeader::new(format!("Untested public functions ({})", coverage.untested_public.len()))
+                .default_open(true)
+                .show(ui, |ui| element_links(ui, &coverage.untested_public, &mut clicked));
+        });
+        
+        if let Some(element_id) = clicked {
+            app.visualization_state.selected_element = Some(element_id);
+            app.view_mode = ViewMode::Visualization;
+        }
+    });
+}
+
+fn element_links(ui: &mut egui::Ui, element_ids: &[String], clicked: &mut Option<String>) {
+    for element_id in element_ids {
+        if ui.link(short_name(element_id)).on_hover_text(element_id).clicked() {
+            *clicked = Some(element_id.clone());
+        }
+    }
+}
+
+/// The last two segments of an element ID, e.g. `Type::method`.
+fn short_name(element_id: &str) -> &str {
+    let mut separators = element_id.rmatch_indices("::").map(|(index, _)| index);
+    separators.next();
+    match separators.next() {
+        Some(index) => &element_id[index + 2..],
+        None => element_id,
+    }
+}
@@ -1,4 +1,6 @@
 use crate::app::{App, ViewMode};
+use crate::parser::{CfgSet, TARGET_ARCHES, TARGET_OSES};
+use crate::project::{LoadPhase, CONFIG_FILE};
 use eframe::egui;
 
 pub fn render(app: &mut App, ctx: &egui::Context) {
@@ -18,6 +20,18 @@ pub fn render(app: &mut App, ctx: &egui::Context) {
                 }
             });
             
+            ui.menu_button("Configuration", |ui| {
+                if cfg_menu(ui, app) {
+                    app.reload_project();
+                }
+            });
+            
+            ui.menu_button("Files", |ui| {
+                if file_filter_menu(ui, app) {
+                    app.reload_project();
+                }
+            });
+            
             ui.separator();
             
             if ui.selectable_label(app.view_mode == ViewMode::Visualization, "Visualization").clicked() {
@@ -27,6 +41,163 @@ pub fn render(app: &mut App, ctx: &egui::Context) {
             if ui.selectable_label(app.view_mode == ViewMode::Editor, "Editor").clicked() {
                 app.view_mode = ViewMode::Editor;
             }
+            
+            ui.separator();
+            
+            let diagnostics = app.project.diagnostics.len();
+            let text = if diagnostics == 0 {
+                egui::RichText::new("Diagnostics")
+            } else {
+                egui::RichText::new(format!("Diagnostics ({})", diagnostics)).color(egui::Color32::from_rgb(230, 180, 80))
+            };
+            if ui.selectable_label(app.show_diagnostics, text).clicked() {
+                app.show_diagnostics = !app.show_diagnostics;
+            }
+            if ui.selectable_label(app.show_tests, "Tests").clicked() {
+                app.show_tests = !app.show_tests;
+            }
+            if ui.selectable_label(app.show_docs, "Docs").clicked() {
+                app.show_docs = !app.show_docs;
+            }
+            let dead_code = app.project.dead_code.unreachable.len();
+            let text = if dead_code == 0 { "Dead Code".to_string() } else { format!("Dead Code ({})", dead_code) };
+            if ui.selectable_label(app.show_dead_code, text).clicked() {
+                app.show_dead_code = !app.show_dead_code;
+            }
+            
+            if app.loader.is_some() {
+                ui.separator();
+                load_progress(ui, app);
+            }
+        });
+    });
+}
+
+/// Shows how far the background load has got, with a button to cancel it.
+fn load_progress(ui: &mut egui::Ui, app: &mut App) {
+    let Some(loader) = &app.loader else {
+        return;
+    };
+    let progress = loader.progress;
+    let total = progress.files_found.max(1) as f32;
+    let (fraction, text) = match progress.phase {
+        LoadPhase::Scanning => (0.0, format!("Scanning: {} files found", progress.files_found)),
+        LoadPhase::Reading => (progress.files_read as f32 / total, format!("Reading {} / {} files", progress.files_read, progress.files_found)),
+        LoadPhase::Parsing => (progress.files_parsed as f32 / total, format!("Parsing {} / {} files", progress.files_parsed, progress.files_found)),
+        LoadPhase::Resolving => (1.0, "Resolving paths…".to_string()),
+    };
+    
+    ui.spinner();
+    ui.add(egui::ProgressBar::new(fraction).desired_width(160.0).text(text));
+    if loader.is_cancelled() {
+        ui.weak("Cancelling…");
+    } else if ui.button("Cancel").on_hover_text(format!("Stop loading {}", loader.path)).clicked() {
+        loader.cancel();
+    }
+}
+
+/// Edits the cfg set the project is parsed with, returning whether it changed.
+fn cfg_menu(ui: &mut egui::Ui, app: &mut App) -> bool {
+    let before = app.project.cfg.clone();
+    let cfg = &mut app.project.cfg;
+    
+    ui.checkbox(&mut cfg.test, "test");
+    ui.checkbox(&mut cfg.debug_assertions, "debug_assertions");
+    
+    ui.separator();
+    egui::ComboBox::from_label("target_os")
+        .selected_text(cfg.target_os.clone())
+        .show_ui(ui, |ui| {
+            for os in TARGET_OSES {
+                ui.selectable_value(&mut cfg.target_os, os.to_string(), os);
+            }
+        });
+    egui::ComboBox::from_label("target_arch")
+        .selected_text(cfg.target_arch.clone())
+        .show_ui(ui, |ui| {
+            for arch in TARGET_ARCHES {
+                ui.selectable_value(&mut cfg.target_arch, arch.to_string(), arch);
+            }
         });
+    
+    ui.separator();
+    ui.label("Features:");
+    if app.project.cfg_features.is_empty() {
+        ui.weak("No features are used in cfg attributes");
+    }
+    for feature in &app.project.cfg_features {
+        let mut enabled = cfg.features.contains(feature);
+        if ui.checkbox(&mut enabled, feature.as_str()).changed() {
+            if enabled {
+                cfg.features.insert(feature.clone());
+            } else {
+                cfg.features.remove(feature);
+            }
+        }
+    }
+    
+    ui.separator();
+    if ui.button("Reset to Host Defaults").clicked() {
+        *cfg = CfgSet::default();
+    }
+    
+    app.project.cfg != before
+}
+
+/// Edits the include and exclude globs of the project's files, returning
+/// whether they should be applied.
+fn file_filter_menu(ui: &mut egui::Ui, app: &mut App) -> bool {
+    let filter = &mut app.project.file_filter;
+    
+    ui.label("Include (every file when empty):");
+    glob_list(ui, "include_globs", &mut filter.include);
+    ui.label("Exclude:");
+    glob_list(ui, "exclude_globs", &mut filter.exclude);
+    ui.weak("Paths are relative to the project, e.g. benches/** or **/generated/**.");
+    ui.weak(".gitignore, .ignore and target/ are always skipped.");
+    
+    ui.separator();
+    let mut apply = false;
+    ui.horizontal(|ui| {
+        apply = ui.button("Apply").clicked();
+        let save = ui.add_enabled(app.project.project_path.is_some(), egui::Button::new(format!("Save to {}", CONFIG_FILE)));
+        if save.clicked() {
+            if let Some(path) = &app.project.project_path {
+                app.config_message = Some(match app.project.file_filter.save(path) {
+                    Ok(()) => format!("Saved to {}", CONFIG_FILE),
+                    Err(error) => format!("Couldn't save {}: {}", CONFIG_FILE, error),
+                });
+            }
+        }
     });
+    if let Some(message) = &app.config_message {
+        ui.weak(message);
+    }
+    
+    if apply {
+        let filter = &mut app.project.file_filter;
+        filter.include.retain(|glob| !glob.trim().is_empty());
+        filter.exclude.retain(|glob| !glob.trim().is_empty());
+    }
+    apply
+}
+
+fn glob_list(ui: &mut egui::Ui, id: &str, globs: &mut Vec<String>) {
+    let mut removed = None;
+    for (i, glob) in globs.iter_mut().enumerate() {
+        ui.push_id((id, i), |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(glob);
+                if ui.small_button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+        });
+    }
+    if let Some(i) = removed {
+        globs.remove(i);
+    }
+    if ui.small_button("+ Add glob").clicked() {
+        globs.push(String::new());
+    }
 }
@@ -1,12 +1,23 @@
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
-    parser::ElementType,
+    parser::{ElementType, Metric, RelationshipType},
     project::Project,
     visualization::state::VisualizationState,
 };
 
+/// How an element stands out in the unsafe audit or error flow views.
+#[derive(Clone, Copy)]
+enum Emphasis {
+    Normal,
+    Highlighted(egui::Color32),
+    Dimmed,
+}
+
+const PRIMARY_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgb(235, 60, 60);
+const SECONDARY_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgb(255, 150, 60);
+
 pub fn draw(
     ui: &mut egui::Ui,
     project: &Project,
@@ -15,8 +26,36 @@ pub fn draw(
     response: &egui::Response,
 ) {
     let painter = ui.painter();
+    let unsafe_audit = state.unsafe_audit;
+    if state.error_flow {
+        state.update_error_sources(project);
+    }
+    let error_flow = state.error_flow.then(|| error_flow(project, state));
+    let emphasis = |element: &crate::project::Element| {
+        if unsafe_audit {
+            let audit = &project.unsafe_audit;
+            if audit.unsafe_elements.contains(&element.id) {
+                Emphasis::Highlighted(PRIMARY_HIGHLIGHT)
+            } else if audit.callers.contains(&element.id) {
+                Emphasis::Highlighted(SECONDARY_HIGHLIGHT)
+            } else {
+                Emphasis::Dimmed
+            }
+        } else if let Some((error_types, fallible)) = &error_flow {
+            if error_types.contains(element.id.as_str()) {
+                Emphasis::Highlighted(PRIMARY_HIGHLIGHT)
+            } else if fallible.contains(element.id.as_str()) {
+                Emphasis::Highlighted(SECONDARY_HIGHLIGHT)
+            } else {
+                Emphasis::Dimmed
+            }
+        } else {
+            Emphasis::Normal
+        }
+    };
     
-    // Draw file background circles first
+    // Draw crate and file background areas first
+    draw_crate_backgrounds(painter, project, element_positions, state.zoom);
     draw_file_backgrounds(painter, project, element_positions, state.zoom);
     
     // Draw elements with improved styling
@@ -25,24 +64,173 @@ pub fn draw(
             let is_selected = state.selected_element.as_ref() == Some(&element.id);
             let is_hovered = response.hovered() && 
                 response.hover_pos().map_or(false, |mouse_pos| {
-                    (mouse_pos - *pos).length() < get_element_radius(&element.element_type, state.zoom) + 5.0
+                    (mouse_pos - *pos).length() < get_element_radius(element, state) + 5.0
                 });
             
-            // Filter elements based on search text
-            let should_show = state.filter_text.is_empty() || 
-                element.name.to_lowercase().contains(&state.filter_text.to_lowercase()) ||
-                element.file_path.to_lowercase().contains(&state.filter_text.to_lowercase());
-            
-            if !should_show {
+            // Filter elements based on search text and declaration details
+            if !state.should_show(element) {
+                continue;
+            }
+            if state.dead_code_only && !project.dead_code.unreachable.contains(&element.id) {
                 continue;
             }
             
-            draw_element(painter, element, *pos, is_selected, is_hovered, state);
+            draw_element(painter, element, *pos, is_selected, is_hovered, state, emphasis(element));
             
-            // Handle clicks for selection
+            if is_hovered {
+                show_element_tooltip(ui.ctx(), project, element);
+            }
+            
+            // Handle clicks for selection; double-clicking a type shows its fields or variants
             if response.clicked() && is_hovered {
                 state.selected_element = Some(element.id.clone());
             }
+            if response.double_clicked() && is_hovered && element.shape.is_some() {
+                state.toggle_expanded(&element.id);
+            }
+        }
+    }
+}
+
+/// Shows an element's declaration details next to the pointer.
+fn show_element_tooltip(ctx: &egui::Context, project: &Project, element: &crate::project::Element) {
+    let details = &element.details;
+    egui::show_tooltip_at_pointer(ctx, egui::Id::new("element_tooltip"), |ui| {
+        ui.strong(&element.name);
+        ui.label(format!("{:?} · {}", element.element_type, details.visibility.label()));
+        
+        if let Some(signature) = &details.signature {
+            ui.monospace(signature);
+        }
+        if !details.generics.is_empty() {
+            ui.label(format!("Generics: <{}>", details.generics.join(", ")));
+        }
+        
+        let qualifiers: Vec<&str> = [(details.is_async, "async"), (details.is_const, "const"), (details.is_unsafe, "unsafe")]
+            .into_iter()
+            .filter_map(|(set, name)| set.then_some(name))
+            .collect();
+        if !qualifiers.is_empty() {
+            ui.label(qualifiers.join(" "));
+        }
+        if let Some(metrics) = &element.metrics {
+            ui.label(metrics.summary());
+        }
+        if details.await_points > 0 {
+            ui.label(format!("{} await points", details.await_points));
+        }
+        if details.unsafe_blocks > 0 || details.raw_derefs > 0 {
+            ui.label(format!("{} unsafe blocks, {} raw pointer derefs", details.unsafe_blocks, details.raw_derefs));
+        }
+        if let Some(error_type) = &details.error_type {
+            ui.label(format!("Fails with {}, {} `?` operators", error_type, details.try_operators));
+        }
+        for attribute in &details.attributes {
+            ui.monospace(format!("#[{}]", attribute));
+        }
+        
+        if element.file_path.is_empty() {
+            ui.weak(format!("external: {}", element.id));
+        } else {
+            if let Some(root) = project.crate_root_of(&element.module_path).filter(|root| !root.package.is_empty()) {
+                ui.weak(format!("{} ({})", root.package, root.kind.label()));
+            }
+            ui.weak(format!("{}:{}", element.file_path, element.span.start_line));
+        }
+    });
+}
+
+/// Error types and the functions failing with them, for the error flow view.
+/// With a function selected, only the errors it can receive from its callees
+/// and the callees they come from; otherwise every `Result`-returning
+/// function and the error types they return or convert between.
+fn error_flow<'a>(project: &'a Project, state: &VisualizationState) -> (HashSet<&'a str>, HashSet<&'a str>) {
+    let mut error_types = HashSet::new();
+    let mut fallible = HashSet::new();
+    
+    let selected = state.selected_element.as_deref()
+        .and_then(|id| state.traced_element(project, id))
+        .filter(|e| e.element_type.is_function());
+    match selected {
+        Some(function) => {
+            error_types.extend(project.error_type_of(&function.id));
+            fallible.insert(function.id.as_str());
+            for source in state.error_sources() {
+                if source.reaches_caller {
+                    if let Some(element) = state.traced_element(project, &source.function_id) {
+                        fallible.insert(element.id.as_str());
+                    }
+                    if let Some(element) = state.traced_element(project, &source.error_type) {
+                        error_types.insert(element.id.as_str());
+                    }
+                }
+            }
+        },
+        None => {
+            for rel in &project.relationships {
+                match rel.relationship_type {
+                    RelationshipType::ReturnsError => {
+                        error_types.insert(rel.target_id.as_str());
+                    },
+                    RelationshipType::ConvertsFrom => {
+                        error_types.insert(rel.source_id.as_str());
+                        error_types.insert(rel.target_id.as_str());
+                    },
+                    _ => {},
+                }
+            }
+            fallible.extend(project.elements.iter()
+                .filter(|e| e.element_type.is_function() && e.details.error_type.is_some())
+                .map(|e| e.id.as_str()));
+        },
+    }
+    
+    (error_types, fallible)
+}
+
+/// Outlines each crate of a workspace around the areas of its files, so
+/// crates read as the top-level clusters. A single crate isn't outlined.
+fn draw_crate_backgrounds(
+    painter: &egui::Painter,
+    project: &Project,
+    element_positions: &HashMap<String, egui::Pos2>,
+    zoom: f32,
+) {
+    if project.packages.len() < 2 {
+        return;
+    }
+    
+    let mut crate_bounds: HashMap<&str, egui::Rect> = HashMap::new();
+    for element in &project.elements {
+        let (Some(pos), Some(package)) = (element_positions.get(&element.id), project.package_of(element)) else {
+            continue;
+        };
+        crate_bounds.entry(package)
+            .and_modify(|rect| rect.extend_with(*pos))
+            .or_insert_with(|| egui::Rect::from_min_max(*pos, *pos));
+    }
+    
+    for (package, bounds) in crate_bounds {
+        let rect = bounds.expand(60.0 * zoom);
+        painter.rect_filled(
+            rect,
+            14.0 * zoom,
+            egui::Color32::from_rgba_unmultiplied(60, 80, 60, 30)
+        );
+        painter.rect_stroke(
+            rect,
+            14.0 * zoom,
+            egui::Stroke::new(1.5 * zoom, egui::Color32::from_rgba_unmultiplied(110, 150, 110, 140))
+        );
+        
+        if zoom > 0.15 {
+            painter.text(
+                egui::pos2(rect.min.x + 12.0 * zoom, rect.min.y + 14.0 * zoom),
+                egui::Align2::LEFT_CENTER,
+                package,
+                egui::FontId::proportional(16.0 * zoom.clamp(0.6, 1.4)),
+                egui::Color32::from_rgb(170, 210, 170)
+            );
         }
     }
 }
@@ -58,6 +246,10 @@ fn draw_file_backgrounds(
     
     for element in &project.elements {
         if let Some(pos) = element_positions.get(&element.id) {
+            // External items have no file to group them in
+            if element.file_path.is_empty() {
+                continue;
+            }
             let entry = file_bounds.entry(element.file_path.clone()).or_insert((*pos, *pos));
             entry.0.x = entry.0.x.min(pos.x);
             entry.0.y = entry.0.y.min(pos.y);
@@ -108,10 +300,19 @@ fn draw_element(
     is_selected: bool,
     is_hovered: bool,
     state: &VisualizationState,
+    emphasis: Emphasis,
 ) {
     // Calculate element appearance
-    let (base_color, shape) = get_element_style(&element.element_type);
-    let radius = get_element_radius(&element.element_type, state.zoom);
+    let (mut base_color, shape) = get_element_style(&element.element_type);
+    if let (Some(metric), Some(metrics)) = (state.color_by, &element.metrics) {
+        base_color = metric_color(metrics.get(metric), metric);
+    }
+    match emphasis {
+        Emphasis::Normal => {},
+        Emphasis::Highlighted(color) => base_color = color,
+        Emphasis::Dimmed => base_color = base_color.linear_multiply(0.25),
+    }
+    let radius = get_element_radius(element, state);
     
     let color = if is_selected {
         egui::Color32::WHITE
@@ -158,6 +359,19 @@ fn draw_element(
                 egui::Stroke::NONE,
             ));
         },
+        ElementShape::Hexagon => {
+            let points = (0..6)
+                .map(|i| {
+                    let angle = std::f32::consts::FRAC_PI_3 * i as f32;
+                    pos + egui::vec2(angle.cos(), angle.sin()) * final_radius
+                })
+                .collect();
+            painter.add(egui::epaint::PathShape::convex_polygon(
+                points,
+                color,
+                egui::Stroke::NONE,
+            ));
+        },
     }
     
     // Draw border for important elements or selection
@@ -184,8 +398,58 @@ fn draw_element(
         }
     }
     
+    // Async functions and tasks get a thin outer ring, so the concurrent parts
+    // of a program stand out next to the call graph
+    if element.details.is_async {
+        painter.circle_stroke(pos, final_radius + 5.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(80, 220, 160)));
+    }
+    
     // Draw labels with better visibility
-    draw_element_label(painter, element, pos, state.should_draw_labels(), is_selected, is_hovered, state.zoom);
+    draw_element_label(painter, element, pos, state, is_selected, is_hovered);
+    
+    if state.expanded_elements.contains(&element.id) {
+        if let Some(shape) = &element.shape {
+            draw_members(painter, &shape.member_labels(), pos, final_radius, color, state.zoom);
+        }
+    }
+}
+
+/// Draws a type's fields or variants as a compartment hanging under its node.
+fn draw_members(
+    painter: &egui::Painter,
+    members: &[String],
+    pos: egui::Pos2,
+    radius: f32,
+    color: egui::Color32,
+    zoom: f32,
+) {
+    let font_id = egui::FontId::monospace(10.0 * zoom.clamp(0.6, 1.2));
+    let padding = 6.0 * zoom.min(1.0);
+    
+    let galleys: Vec<_> = if members.is_empty() {
+        vec![painter.layout_no_wrap("(no fields)".to_string(), font_id, egui::Color32::from_gray(150))]
+    } else {
+        members.iter()
+            .map(|member| painter.layout_no_wrap(member.clone(), font_id.clone(), egui::Color32::from_gray(220)))
+            .collect()
+    };
+    
+    let width = galleys.iter().map(|g| g.size().x).fold(0.0, f32::max) + padding * 2.0;
+    let height = galleys.iter().map(|g| g.size().y).sum::<f32>() + padding * 2.0;
+    let rect = egui::Rect::from_min_size(
+        egui::pos2(pos.x - width / 2.0, pos.y + radius + 6.0),
+        egui::vec2(width, height),
+    );
+    
+    painter.rect_filled(rect, 4.0, egui::Color32::from_rgba_unmultiplied(25, 25, 35, 230));
+    painter.rect_stroke(rect, 4.0, egui::Stroke::new(1.0, color.linear_multiply(0.8)));
+    
+    let mut y = rect.min.y + padding;
+    for galley in galleys {
+        let line_height = galley.size().y;
+        painter.galley(egui::pos2(rect.min.x + padding, y), galley);
+        y += line_height;
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -194,43 +458,81 @@ enum ElementShape {
     Square,
     Diamond,
     Triangle,
+    Hexagon,
 }
 
 fn get_element_style(element_type: &ElementType) -> (egui::Color32, ElementShape) {
     match element_type {
         ElementType::Function => (egui::Color32::from_rgb(100, 170, 255), ElementShape::Circle),
+        ElementType::Method => (egui::Color32::from_rgb(120, 210, 255), ElementShape::Circle),
+        ElementType::AssociatedFunction => (egui::Color32::from_rgb(80, 130, 220), ElementShape::Circle),
         ElementType::Struct => (egui::Color32::from_rgb(255, 150, 100), ElementShape::Square),
         ElementType::Enum => (egui::Color32::from_rgb(150, 255, 100), ElementShape::Diamond),
         ElementType::Trait => (egui::Color32::from_rgb(255, 100, 180), ElementShape::Triangle),
+        ElementType::ExternalTrait => (egui::Color32::from_rgb(170, 120, 150), ElementShape::Triangle),
         ElementType::Impl => (egui::Color32::from_rgb(180, 100, 255), ElementShape::Circle),
         ElementType::Module => (egui::Color32::from_rgb(255, 200, 100), ElementShape::Square),
+        ElementType::Const => (egui::Color32::from_rgb(230, 230, 120), ElementShape::Diamond),
+        ElementType::Static => (egui::Color32::from_rgb(255, 120, 100), ElementShape::Diamond),
+        ElementType::TypeAlias => (egui::Color32::from_rgb(255, 170, 200), ElementShape::Triangle),
+        ElementType::Macro => (egui::Color32::from_rgb(100, 230, 200), ElementShape::Hexagon),
+        ElementType::Union => (egui::Color32::from_rgb(220, 180, 130), ElementShape::Square),
+        ElementType::ExternBlock => (egui::Color32::from_rgb(160, 160, 180), ElementShape::Hexagon),
+        ElementType::ForeignFunction => (egui::Color32::from_rgb(140, 150, 200), ElementShape::Circle),
+        ElementType::Task => (egui::Color32::from_rgb(80, 220, 160), ElementShape::Hexagon),
     }
 }
 
-fn get_element_radius(element_type: &ElementType, zoom: f32) -> f32 {
-    let base_size = match element_type {
+/// Radius of an element's node, grown by the metric nodes are sized by.
+fn get_element_radius(element: &crate::project::Element, state: &VisualizationState) -> f32 {
+    let base_size = match element.element_type {
         ElementType::Module => 18.0,
-        ElementType::Struct | ElementType::Enum | ElementType::Trait => 14.0,
-        ElementType::Function | ElementType::Impl => 10.0,
+        ElementType::Struct | ElementType::Enum | ElementType::Trait | ElementType::Union => 14.0,
+        ElementType::ExternBlock | ElementType::ExternalTrait => 12.0,
+        ElementType::Function | ElementType::Impl | ElementType::ForeignFunction | ElementType::Macro => 10.0,
+        ElementType::TypeAlias | ElementType::Const | ElementType::Static => 9.0,
+        ElementType::Method | ElementType::AssociatedFunction | ElementType::Task => 8.0,
+    };
+    
+    let metric_scale = match (state.size_by, &element.metrics) {
+        (Some(metric), Some(metrics)) => 1.0 + metric_ratio(metrics.get(metric), metric).min(2.0) * 0.5,
+        _ => 1.0,
     };
     
-    base_size * zoom.clamp(0.5, 2.0)
+    base_size * metric_scale * state.zoom.clamp(0.5, 2.0)
+}
+
+/// How far `value` has come towards the metric's threshold; 1.0 at the threshold.
+fn metric_ratio(value: usize, metric: Metric) -> f32 {
+    value as f32 / metric.threshold() as f32
+}
+
+/// Green for trivial functions, through yellow, to red at the metric's threshold.
+fn metric_color(value: usize, metric: Metric) -> egui::Color32 {
+    let ratio = metric_ratio(value, metric).min(1.0);
+    if ratio < 0.5 {
+        let t = ratio * 2.0;
+        egui::Color32::from_rgb((100.0 + 155.0 * t) as u8, 210, 100)
+    } else {
+        let t = (ratio - 0.5) * 2.0;
+        egui::Color32::from_rgb(255, (210.0 - 130.0 * t) as u8, (100.0 - 20.0 * t) as u8)
+    }
 }
 
 fn draw_element_label(
     painter: &egui::Painter,
     element: &crate::project::Element,
     pos: egui::Pos2,
-    should_show_labels: bool,
+    state: &VisualizationState,
     is_selected: bool,
     is_hovered: bool,
-    zoom: f32,
 ) {
-    let should_draw = should_show_labels || is_selected || is_hovered;
+    let zoom = state.zoom;
+    let should_draw = state.should_draw_labels() || is_selected || is_hovered;
     let min_zoom_for_labels = 0.4;
     
     if should_draw && zoom > min_zoom_for_labels {
-        let label_offset = egui::vec2(0.0, -get_element_radius(&element.element_type, zoom) - 15.0);
+        let label_offset = egui::vec2(0.0, -get_element_radius(element, state) - 15.0);
         let label_pos = pos + label_offset;
         
         // Determine text size and style
@@ -279,8 +581,11 @@ fn draw_element_label(
         
         // Show element type for selected items
         if is_selected && zoom > 0.6 {
-            let type_text = format!("{:?}", element.element_type);
-            let type_pos = pos + egui::vec2(0.0, get_element_radius(&element.element_type, zoom) + 20.0);
+            let type_text = match element.receiver {
+                Some(receiver) => format!("{:?} ({})", element.element_type, receiver.label()),
+                None => format!("{:?}", element.element_type),
+            };
+            let type_pos = pos + egui::vec2(0.0, get_element_radius(element, state) + 20.0);
             
             painter.text(
                 type_pos,
@@ -128,6 +128,8 @@ fn draw_element_relationships(
             }
             
             let (stroke, arrow_style) = get_relationship_style(&relationship.relationship_type, is_selected);
+            // Derived impls are dashed to set them apart from hand-written ones
+            let arrow_style = if relationship.derived { ArrowStyle::Dashed } else { arrow_style };
             
             // Draw thinner lines for element relationships to reduce visual noise
             let element_stroke = egui::Stroke::new(stroke.width * 0.7, stroke.color);
@@ -157,6 +159,42 @@ fn get_relationship_style(rel_type: &RelationshipType, is_selected: bool) -> (eg
             egui::Stroke::new(base_thickness * 0.8, egui::Color32::from_rgba_unmultiplied(255, 200, 100, alpha)),
             ArrowStyle::None
         ),
+        RelationshipType::AssociatedWith => (
+            egui::Stroke::new(base_thickness * 0.6, egui::Color32::from_rgba_unmultiplied(120, 210, 255, alpha)),
+            ArrowStyle::None
+        ),
+        RelationshipType::Uses => (
+            egui::Stroke::new(base_thickness * 0.8, egui::Color32::from_rgba_unmultiplied(200, 150, 255, alpha)),
+            ArrowStyle::Dashed
+        ),
+        RelationshipType::References => (
+            egui::Stroke::new(base_thickness * 0.8, egui::Color32::from_rgba_unmultiplied(180, 180, 180, alpha)),
+            ArrowStyle::Dashed
+        ),
+        RelationshipType::DependsOn => (
+            egui::Stroke::new(base_thickness, egui::Color32::from_rgba_unmultiplied(230, 120, 200, alpha)),
+            ArrowStyle::Simple
+        ),
+        RelationshipType::Extends => (
+            egui::Stroke::new(base_thickness * 1.2, egui::Color32::from_rgba_unmultiplied(255, 220, 120, alpha)),
+            ArrowStyle::Double
+        ),
+        RelationshipType::BoundedBy => (
+            egui::Stroke::new(base_thickness * 0.8, egui::Color32::from_rgba_unmultiplied(255, 180, 150, alpha)),
+            ArrowStyle::Dashed
+        ),
+        RelationshipType::Spawns => (
+            egui::Stroke::new(base_thickness * 1.4, egui::Color32::from_rgba_unmultiplied(80, 220, 160, alpha)),
+            ArrowStyle::Double
+        ),
+        RelationshipType::ReturnsError => (
+            egui::Stroke::new(base_thickness, egui::Color32::from_rgba_unmultiplied(255, 110, 110, alpha)),
+            ArrowStyle::Dashed
+        ),
+        RelationshipType::ConvertsFrom => (
+            egui::Stroke::new(base_thickness * 1.2, egui::Color32::from_rgba_unmultiplied(255, 170, 90, alpha)),
+            ArrowStyle::Simple
+        ),
     }
 }
 
@@ -322,6 +360,15 @@ pub fn draw_relationships_with_ui(_ui: &mut egui::Ui, painter: &egui::Painter, p
                 RelationshipType::Imports => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(200, 255, 200, 180)),
                 RelationshipType::Implements => egui::Stroke::new(2.0, egui::Color32::from_rgba_unmultiplied(255, 200, 200, 180)),
                 RelationshipType::Contains => egui::Stroke::new(3.0, egui::Color32::from_rgba_unmultiplied(255, 255, 200, 180)),
+                RelationshipType::AssociatedWith => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(200, 230, 255, 180)),
+                RelationshipType::Uses => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(220, 200, 255, 180)),
+                RelationshipType::References => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(210, 210, 210, 180)),
+                RelationshipType::DependsOn => egui::Stroke::new(1.5, egui::Color32::from_rgba_unmultiplied(255, 200, 240, 180)),
+                RelationshipType::Extends => egui::Stroke::new(2.0, egui::Color32::from_rgba_unmultiplied(255, 235, 180, 180)),
+                RelationshipType::BoundedBy => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(255, 210, 190, 180)),
+                RelationshipType::Spawns => egui::Stroke::new(2.0, egui::Color32::from_rgba_unmultiplied(170, 240, 210, 180)),
+                RelationshipType::ReturnsError => egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(255, 180, 180, 180)),
+                RelationshipType::ConvertsFrom => egui::Stroke::new(1.5, egui::Color32::from_rgba_unmultiplied(255, 210, 170, 180)),
             };
             
             painter.line_segment([source_pos, target_pos], stroke);
@@ -1,4 +1,5 @@
 use eframe::egui;
+use crate::parser::{Fields, RelationshipType, TypeShape};
 use crate::project::Project;
 use crate::visualization::VisualizationState;
 
@@ -62,7 +63,8 @@ pub fn draw_status_original(ui: &mut egui::Ui, parent_rect: egui::Rect, project:
 }
 
 // Add new function matching the call in renderer.rs
-pub fn draw_status(ui: &mut egui::Ui, project: &Project, state: &VisualizationState) {
+pub fn draw_status(ui: &mut egui::Ui, project: &Project, state: &mut VisualizationState) {
+    state.update_error_sources(project);
     let status_height = 25.0;
     let parent_rect = ui.available_rect_before_wrap();
     let status_rect = egui::Rect::from_min_size(
@@ -96,7 +98,80 @@ pub fn draw_status(ui: &mut egui::Ui, project: &Project, state: &VisualizationSt
     // Draw selected element info
     if let Some(element_id) = &state.selected_element {
         if let Some(element) = project.elements.iter().find(|e| &e.id == element_id) {
-            let selected_text = format!("Selected: {}", element.name);
+            let mut selected_text = if element.file_path.is_empty() {
+                format!("Selected: {}  (external)", element.id)
+            } else {
+                format!(
+                    "Selected: {}  ({}:{}, {} lines)",
+                    element.name,
+                    element.file_path,
+                    element.span.start_line,
+                    element.span.line_count()
+                )
+            };
+            
+            // For types, summarize the behaviour attached through impl blocks
+            let method_count = project.relationships.iter()
+                .filter(|r| r.relationship_type == RelationshipType::AssociatedWith && &r.target_id == element_id)
+                .count();
+            if method_count > 0 {
+                selected_text.push_str(&format!("  |  {} methods", method_count));
+            }
+            // Derived and hand-written impls of a trait, or the traits a type derives
+            let derived_count = project.relationships.iter()
+                .filter(|r| r.derived && (&r.target_id == element_id || &r.source_id == element_id))
+                .count();
+            if derived_count > 0 {
+                selected_text.push_str(&format!("  |  {} derived impls", derived_count));
+            }
+            match &element.shape {
+                Some(TypeShape::Struct(Fields::Named(fields) | Fields::Tuple(fields))) => {
+                    selected_text.push_str(&format!("  |  {} fields (double-click to show)", fields.len()));
+                },
+                Some(TypeShape::Enum(variants)) => {
+                    selected_text.push_str(&format!("  |  {} variants (double-click to show)", variants.len()));
+                },
+                _ => {}
+            }
+            if let Some(metrics) = &element.metrics {
+                selected_text.push_str(&format!("  |  {}", metrics.summary()));
+            }
+            let details = &element.details;
+            if details.is_async {
+                selected_text.push_str(&format!("  |  async, {} await points", details.await_points));
+            }
+            let spawn_count = project.relationships.iter()
+                .filter(|r| r.relationship_type == RelationshipType::Spawns && &r.source_id == element_id)
+                .count();
+            if spawn_count > 0 {
+                selected_text.push_str(&format!("  |  spawns {} tasks", spawn_count));
+            }
+            if let Some(tests) = project.test_coverage.tested_by.get(element_id) {
+                selected_text.push_str(&format!("  |  tested by {} tests", tests.len()));
+            }
+            if let Some(error_type) = &details.error_type {
+                selected_text.push_str(&format!("  |  fails with {}, {} `?`", error_type, details.try_operators));
+                let received: Vec<String> = state.error_sources().iter()
+                    .filter(|source| source.reaches_caller)
+                    .map(|source| match state.traced_element(project, &source.error_type) {
+                        Some(error_type) => error_type.name.clone(),
+                        None => source.error_type.clone(),
+                    })
+                    .collect();
+                if !received.is_empty() {
+                    selected_text.push_str(&format!("  |  can receive: {}", received.join(", ")));
+                }
+            }
+            if details.contains_unsafe() {
+                selected_text.push_str(&format!(
+                    "  |  unsafe: {}{} blocks, {} raw derefs",
+                    if details.is_unsafe { "declared, " } else { "" },
+                    details.unsafe_blocks,
+                    details.raw_derefs
+                ));
+            } else if project.unsafe_audit.callers.contains(element_id) {
+                selected_text.push_str("  |  calls unsafe code");
+            }
             ui.painter().text(
                 egui::pos2(status_rect.right() - 10.0, status_rect.center().y),
                 egui::Align2::RIGHT_CENTER,
@@ -1,5 +1,5 @@
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use crate::{project::Project, LayoutSettings};
 
 pub struct ForceDirectedLayout {
@@ -10,6 +10,8 @@ pub struct ForceDirectedLayout {
     pub is_stable: bool,
     pub iteration_count: u32,
     pub max_iterations: u32,
+    /// How many elements the project had when positions were last placed
+    pub element_count: usize,
 }
 
 impl Default for ForceDirectedLayout {
@@ -22,6 +24,7 @@ impl Default for ForceDirectedLayout {
             is_stable: false,
             iteration_count: 0,
             max_iterations: 1000,
+            element_count: 0,
         }
     }
 }
@@ -37,34 +40,61 @@ impl ForceDirectedLayout {
         self.forces.clear();
         self.iteration_count = 0;
         self.is_stable = false;
+        self.element_count = 0;
     }
 
-    pub fn initialize_positions(&mut self, elements: &[crate::project::Element], center: egui::Pos2, bounds: egui::Rect) {
+    pub fn initialize_positions(&mut self, project: &Project, center: egui::Pos2, bounds: egui::Rect) {
         self.positions.clear();
         self.velocities.clear();
         self.forces.clear();
+        self.add_missing_positions(project, center, bounds);
+    }
+
+    /// Places the elements that have no position yet, such as those parsed
+    /// since the last frame while a project loads, where they would start
+    /// out; the others stay where the layout moved them.
+    pub fn add_missing_positions(&mut self, project: &Project, center: egui::Pos2, bounds: egui::Rect) {
+        // Each crate of a workspace starts on a circle of its own around a
+        // point of a larger circle; a single crate fills the whole circle
+        let clusters = crate_clusters(project);
+        let cluster_count = clusters.len();
+        let cluster_radius = bounds.width().min(bounds.height()) * 0.35;
+        let radius = if cluster_count > 1 {
+            cluster_radius * 0.8 / (cluster_count as f32).sqrt()
+        } else {
+            bounds.width().min(bounds.height()) * 0.3
+        };
         
-        // Initialize positions in a circle or grid pattern
-        let radius = bounds.width().min(bounds.height()) * 0.3;
-        let count = elements.len();
-        
-        for (i, element) in elements.iter().enumerate() {
-            let angle = if count > 1 {
-                2.0 * std::f32::consts::PI * i as f32 / count as f32
+        for (cluster_index, elements) in clusters.values().enumerate() {
+            let cluster_center = if cluster_count > 1 {
+                let angle = 2.0 * std::f32::consts::PI * cluster_index as f32 / cluster_count as f32;
+                center + egui::vec2(cluster_radius * angle.cos(), cluster_radius * angle.sin())
             } else {
-                0.0
+                center
             };
+            let count = elements.len();
             
-            let offset = egui::vec2(
-                radius * angle.cos(),
-                radius * angle.sin()
-            );
-            
-            self.positions.insert(element.id.clone(), center + offset);
-            self.velocities.insert(element.id.clone(), egui::Vec2::ZERO);
-            self.forces.insert(element.id.clone(), egui::Vec2::ZERO);
+            for (i, element) in elements.iter().enumerate() {
+                let angle = if count > 1 {
+                    2.0 * std::f32::consts::PI * i as f32 / count as f32
+                } else {
+                    0.0
+                };
+                
+                let offset = egui::vec2(
+                    radius * angle.cos(),
+                    radius * angle.sin()
+                );
+                
+                if !self.positions.contains_key(&element.id) {
+                    self.positions.insert(element.id.clone(), cluster_center + offset);
+                    self.velocities.insert(element.id.clone(), egui::Vec2::ZERO);
+                    self.forces.insert(element.id.clone(), egui::Vec2::ZERO);
+                }
+            }
         }
         
+        self.element_count = project.elements.len();
         self.iteration_count = 0;
         self.is_stable = false;
     }
@@ -93,6 +123,9 @@ impl ForceDirectedLayout {
         // Calculate attractive forces from relationships
         self.calculate_attractive_forces(project);
         
+        // Keep the crates of a workspace together
+        self.calculate_cluster_forces(project);
+        
         // Apply forces and update positions
         let mut max_displacement: f32 = 0.0;
         
@@ -187,6 +220,30 @@ impl ForceDirectedLayout {
         }
     }
 
+    fn calculate_cluster_forces(&mut self, project: &Project) {
+        let clusters = crate_clusters(project);
+        if clusters.len() < 2 {
+            return;
+        }
+        
+        for elements in clusters.values() {
+            let positions: Vec<egui::Pos2> = elements.iter()
+                .filter_map(|element| self.positions.get(&element.id).copied())
+                .collect();
+            if positions.is_empty() {
+                continue;
+            }
+            let sum = positions.iter().fold(egui::Vec2::ZERO, |sum, pos| sum + pos.to_vec2());
+            let centroid = (sum / positions.len() as f32).to_pos2();
+            
+            for element in elements {
+                if let (Some(position), Some(force)) = (self.positions.get(&element.id), self.forces.get_mut(&element.id)) {
+                    *force += (centroid - *position) * self.settings.force_strength * 0.5;
+                }
+            }
+        }
+    }
+
     pub fn get_positions(&self) -> &HashMap<String, egui::Pos2> {
         &self.positions
     }
@@ -196,3 +253,13 @@ impl ForceDirectedLayout {
         self.is_stable
     }
 }
+
+/// Elements grouped by the crate they belong to, in a stable order. External
+/// items and projects without a manifest form a single group.
+fn crate_clusters(project: &Project) -> BTreeMap<&str, Vec<&crate::project::Element>> {
+    let mut clusters: BTreeMap<&str, Vec<&crate::project::Element>> = BTreeMap::new();
+    for element in &project.elements {
+        clusters.entry(project.package_of(element).unwrap_or_default()).or_default().push(element);
+    }
+    clusters
+}
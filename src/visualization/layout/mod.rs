@@ -108,10 +108,14 @@ fn calculate_force_directed_positions(
     if let Ok(mut state) = LAYOUT_STATE.lock() {
         let layout_state = state.get_or_insert_with(LayoutState::default);
 
-        // Initialize if needed
-        if layout_state.force_directed.positions.is_empty() {
-            let bounds = egui::Rect::from_center_size(center, egui::vec2(800.0 * zoom, 600.0 * zoom));
-            layout_state.force_directed.initialize_positions(&project.elements, center, bounds);
+        // Initialize if needed, and place the elements that came in since,
+        // as while a project loads
+        let layout = &mut layout_state.force_directed;
+        let bounds = egui::Rect::from_center_size(center, egui::vec2(800.0 * zoom, 600.0 * zoom));
+        if layout.positions.is_empty() {
+            layout.initialize_positions(project, center, bounds);
+        } else if layout.element_count != project.elements.len() {
+            layout.add_missing_positions(project, center, bounds);
         }
 
         // Update positions using force-directed algorithm
@@ -215,9 +219,20 @@ fn calculate_hierarchical_positions(
         ElementType::Module,
         ElementType::Struct,
         ElementType::Trait,
+        ElementType::ExternalTrait,
         ElementType::Impl,
+        ElementType::Method,
+        ElementType::AssociatedFunction,
         ElementType::Function,
         ElementType::Enum,
+        ElementType::Union,
+        ElementType::TypeAlias,
+        ElementType::Const,
+        ElementType::Static,
+        ElementType::Macro,
+        ElementType::ExternBlock,
+        ElementType::ForeignFunction,
+        ElementType::Task,
     ];
 
     let layer_spacing = 120.0 * zoom;
@@ -4,6 +4,7 @@ mod components;
 mod layout;
 
 pub use state::VisualizationState;
+pub use layout::reset_layout;
 pub use renderer::{VisualizationRenderer, Node, Edge};
 
 use eframe::egui;
@@ -21,6 +22,11 @@ pub fn render_visualization(ui: &mut egui::Ui, project: &Project, state: &mut Vi
             state.animation_progress = 0.0;
         }
     }
+    // Elements parsed since the last frame need time to settle in
+    if state.laid_out_elements != project.elements.len() {
+        state.laid_out_elements = project.elements.len();
+        state.animation_progress = 0.0;
+    }
     
     // Ensure zoom is reasonable
     if state.zoom <= 0.0 || state.zoom.is_nan() {
@@ -119,10 +125,11 @@ fn calculate_project_hash(project: &Project) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
     
+    // Elements stream in while a project loads, and are placed as they come,
+    // so only another project or file set starts the layout over
     let mut hasher = DefaultHasher::new();
+    project.project_path.hash(&mut hasher);
     project.files.len().hash(&mut hasher);
-    project.elements.len().hash(&mut hasher);
-    project.relationships.len().hash(&mut hasher);
     hasher.finish()
 }
 
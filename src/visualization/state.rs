@@ -1,4 +1,8 @@
 use eframe::egui;
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::{ElementType, Metric, Visibility};
+use crate::project::{Element, ErrorSource, Project};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LayoutType {
@@ -15,6 +19,78 @@ impl Default for LayoutType {
     }
 }
 
+/// Narrows the graph down to elements with certain declaration details,
+/// e.g. every `pub` async function.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementFilter {
+    pub element_type: Option<ElementType>,
+    pub visibility: Option<Visibility>,
+    /// Substring of one of the element's attributes, e.g. `test` or `derive`
+    pub attribute: String,
+    pub require_async: bool,
+    pub require_const: bool,
+    pub require_unsafe: bool,
+}
+
+impl ElementFilter {
+    pub fn matches(&self, element: &Element) -> bool {
+        let details = &element.details;
+        self.element_type.as_ref().is_none_or(|element_type| {
+            // Functions of every kind match "Function"
+            element.element_type == *element_type
+                || (*element_type == ElementType::Function && element.element_type.is_function())
+        })
+            && self.visibility.is_none_or(|visibility| details.visibility == visibility)
+            && (self.attribute.is_empty() || details.attributes.iter().any(|attr| attr.contains(self.attribute.as_str())))
+            && (!self.require_async || details.is_async)
+            && (!self.require_const || details.is_const)
+            && (!self.require_unsafe || details.is_unsafe)
+    }
+    
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Kind")
+            .selected_text(self.element_type.as_ref().map_or("Any".to_string(), |t| format!("{:?}", t)))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.element_type, None, "Any");
+                for element_type in ElementType::ALL {
+                    let label = format!("{:?}", element_type);
+                    ui.selectable_value(&mut self.element_type, Some(element_type), label);
+                }
+            });
+        egui::ComboBox::from_label("Visibility")
+            .selected_text(self.visibility.map_or("Any", |v| v.label()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.visibility, None, "Any");
+                for visibility in Visibility::ALL {
+                    ui.selectable_value(&mut self.visibility, Some(visibility), visibility.label());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.require_async, "async");
+            ui.checkbox(&mut self.require_const, "const");
+            ui.checkbox(&mut self.require_unsafe, "unsafe");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Attribute:");
+            ui.text_edit_singleline(&mut self.attribute);
+        });
+        if *self != ElementFilter::default() && ui.button("Clear Filters").clicked() {
+            *self = ElementFilter::default();
+        }
+    }
+}
+
+fn metric_combo(ui: &mut egui::Ui, label: &str, metric: &mut Option<Metric>) {
+    egui::ComboBox::from_label(label)
+        .selected_text(metric.map_or("Nothing", |m| m.label()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(metric, None, "Nothing");
+            for option in Metric::ALL {
+                ui.selectable_value(metric, Some(option), option.label());
+            }
+        });
+}
+
 #[derive(Clone)]
 pub struct VisualizationState {
     pub zoom: f32,
@@ -23,10 +99,55 @@ pub struct VisualizationState {
     pub selected_element: Option<String>,
     pub show_all_relationships: bool,
     pub animation_progress: f32,
+    /// Number of elements the layout last animated in; more arriving while
+    /// the project loads restarts the animation
+    pub laid_out_elements: usize,
     pub dragging: bool,
     pub last_pointer_pos: Option<egui::Pos2>,
     pub filter_text: String,
+    pub element_filter: ElementFilter,
     pub show_labels: bool,
+    /// Function metric that scales node size
+    pub size_by: Option<Metric>,
+    /// Function metric that colours nodes from green to red
+    pub color_by: Option<Metric>,
+    /// Highlights unsafe code and its transitive callers, dimming the rest
+    pub unsafe_audit: bool,
+    /// Highlights error types and the functions returning them; with a
+    /// function selected, only the errors it can receive
+    pub error_flow: bool,
+    /// Hides every element some entry point reaches
+    pub dead_code_only: bool,
+    /// Types whose fields or variants are drawn under their node
+    pub expanded_elements: HashSet<String>,
+    /// Errors the selected function can receive; cleared when the project
+    /// changes
+    pub error_sources: Option<TracedErrors>,
+}
+
+/// The errors a function can receive, traced once per selection.
+#[derive(Clone)]
+pub struct TracedErrors {
+    pub function_id: String,
+    pub sources: Vec<ErrorSource>,
+    /// Where the function and the error types and functions the sources
+    /// name are in `project.elements`
+    pub indices: HashMap<String, usize>,
+}
+
+impl TracedErrors {
+    fn trace(project: &Project, function_id: &str) -> Self {
+        let sources = project.error_sources(function_id);
+        let named: HashSet<&str> = std::iter::once(function_id)
+            .chain(sources.iter().flat_map(|source| [source.error_type.as_str(), source.function_id.as_str()]))
+            .collect();
+        let indices = project.elements.iter()
+            .enumerate()
+            .filter(|(_, element)| named.contains(element.id.as_str()))
+            .map(|(index, element)| (element.id.clone(), index))
+            .collect();
+        Self { function_id: function_id.to_string(), sources, indices }
+    }
 }
 
 impl Default for VisualizationState {
@@ -44,10 +165,19 @@ impl VisualizationState {
             selected_element: None,
             show_all_relationships: false,
             animation_progress: 0.0,
+            laid_out_elements: 0,
             dragging: false,
             last_pointer_pos: None,
             filter_text: String::new(),
+            element_filter: ElementFilter::default(),
             show_labels: true,
+            size_by: None,
+            color_by: None,
+            unsafe_audit: false,
+            error_flow: false,
+            dead_code_only: false,
+            expanded_elements: HashSet::new(),
+            error_sources: None,
         }
     }
     
@@ -55,6 +185,53 @@ impl VisualizationState {
         self.show_labels
     }
     
+    /// Whether `element` passes both the search text and the element filter.
+    pub fn should_show(&self, element: &Element) -> bool {
+        let filter_text = self.filter_text.to_lowercase();
+        let matches_text = filter_text.is_empty()
+            || element.name.to_lowercase().contains(&filter_text)
+            || element.file_path.to_lowercase().contains(&filter_text);
+        
+        matches_text && self.element_filter.matches(element)
+    }
+    
+    /// Traces the errors the selected function can receive, unless they were
+    /// traced for it already.
+    pub fn update_error_sources(&mut self, project: &Project) {
+        let Some(selected) = &self.selected_element else {
+            return;
+        };
+        if self.error_sources.as_ref().is_none_or(|traced| &traced.function_id != selected) {
+            self.error_sources = Some(TracedErrors::trace(project, selected));
+        }
+    }
+    
+    /// The errors the selected function can receive, as of the last
+    /// `update_error_sources`.
+    pub fn error_sources(&self) -> &[ErrorSource] {
+        self.traced_errors().map_or(&[], |traced| &traced.sources)
+    }
+    
+    /// The selected function, or an error type or function its errors come
+    /// from, looked up without a scan of the elements.
+    pub fn traced_element<'a>(&self, project: &'a Project, id: &str) -> Option<&'a Element> {
+        let index = *self.traced_errors()?.indices.get(id)?;
+        project.elements.get(index)
+    }
+    
+    fn traced_errors(&self) -> Option<&TracedErrors> {
+        match (&self.error_sources, &self.selected_element) {
+            (Some(traced), Some(selected)) if &traced.function_id == selected => Some(traced),
+            _ => None,
+        }
+    }
+    
+    pub fn toggle_expanded(&mut self, element_id: &str) {
+        if !self.expanded_elements.remove(element_id) {
+            self.expanded_elements.insert(element_id.to_string());
+        }
+    }
+    
     pub fn update_animation(&mut self, _ctx: &egui::Context) {
         // Simple animation progress
         self.animation_progress += 0.016; // ~60fps
@@ -95,11 +272,26 @@ impl VisualizationState {
         
         ui.checkbox(&mut self.show_all_relationships, "Show File Relationships");
         ui.checkbox(&mut self.show_labels, "Show Element Labels");
+        ui.checkbox(&mut self.unsafe_audit, "Unsafe Audit")
+            .on_hover_text("Red: unsafe code, orange: calls into it");
+        ui.checkbox(&mut self.error_flow, "Error Flow")
+            .on_hover_text("Red: error types, orange: functions returning them. Select a function to see the errors it can receive");
+        ui.checkbox(&mut self.dead_code_only, "Dead Code Only");
+        if !self.expanded_elements.is_empty() && ui.button("Collapse All Types").clicked() {
+            self.expanded_elements.clear();
+        }
         
         ui.separator();
         
         ui.label("Filter Elements:");
         ui.text_edit_singleline(&mut self.filter_text);
+        ui.collapsing("Declaration Filters", |ui| {
+            self.element_filter.ui(ui);
+        });
+        ui.collapsing("Function Metrics", |ui| {
+            metric_combo(ui, "Size by", &mut self.size_by);
+            metric_combo(ui, "Colour by", &mut self.color_by);
+        });
         
         ui.separator();
         